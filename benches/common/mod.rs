@@ -0,0 +1,49 @@
+//! Code shared by the criterion and the divan benchmarks.
+
+use std::sync::OnceLock;
+
+use day10::generator::{generate, GeneratorOptions};
+use day10::Location;
+
+/// The widths and heights of the maps of the `scaling` benchmarks, which
+/// run all implementations of both parts on generated square maps of
+/// growing size and report tiles per second, to show how the solutions
+/// scale. Each map has a loop through half of its tiles.
+pub const SIZES: [usize; 4] = [64, 256, 1024, 4096];
+
+/// The generated map of one of the [`SIZES`]. Generating the largest one
+/// takes a few seconds, so each map is generated once and then kept.
+pub fn generated(size: usize) -> &'static str {
+    static INPUTS: [OnceLock<String>; SIZES.len()] =
+        [const { OnceLock::new() }; SIZES.len()];
+    let i = SIZES.iter().position(|&s| s == size).expect("unknown size");
+    INPUTS[i].get_or_init(|| {
+        let options = GeneratorOptions {
+            width: size,
+            height: size,
+            loop_length: size * size / 2,
+            ..Default::default()
+        };
+        generate(&options).expect("can't generate map").input
+    })
+}
+
+/// The tiles enclosed by a closed path, by the shoelace formula and Pick's
+/// theorem, as `PipeMap::enclosed_count` computed them before it dropped
+/// `unsafe`. It is only kept to compare `shoelace_rust` against.
+pub fn shoelace_unchecked(path: &[Location]) -> usize {
+    let n = path.len();
+    let area = (0..n - 1)
+        .fold(0, |acc, i| {
+            // avoid bound checking is safe here
+            let xi = unsafe { path.get_unchecked(i).x } as isize;
+            let yi = unsafe { path.get_unchecked(i).y } as isize;
+            let x_next = unsafe { path.get_unchecked(i + 1).x } as isize;
+            let y_next = unsafe { path.get_unchecked(i + 1).y } as isize;
+            acc + (yi + y_next) * (xi - x_next)
+        })
+        .unsigned_abs()
+        / 2;
+    // Pick's theorem
+    area - (n - 1) / 2 + 1
+}
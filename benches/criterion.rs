@@ -1,100 +1,151 @@
 // a criterion benchmark for p2, p2_reverse, and p2_maps
 
-use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
-use day10::{p1, p2};
+use criterion::{
+    black_box, criterion_group, criterion_main, BenchmarkId, Criterion,
+    Throughput,
+};
+use day10::{p1, p2, p2_with, PipeMap, Strategy};
 use std::fs::File;
 use std::io::Read;
 
+mod common;
+
+/// The puzzle input, read once per benchmark instead of once per batch.
+fn read_input() -> String {
+    let mut f = File::open("input.txt").expect("can't open file");
+    let mut buf = String::new();
+    f.read_to_string(&mut buf).expect("can't read file");
+    buf
+}
+
 fn bench_p1(c: &mut Criterion) {
+    let input = read_input();
     let mut g = c.benchmark_group("criterion");
-    g.bench_function("part1_rust", |b| {
-        b.iter_batched(
-            || {
-                let mut f = File::open("input.txt").expect("can't open file");
-                let mut buf = String::new();
-                f.read_to_string(&mut buf).expect("can't read file");
-                buf
-            },
-            |f| p1(&f),
-            BatchSize::SmallInput,
-        )
-    });
+    g.bench_function("part1_rust", |b| b.iter(|| p1(black_box(&input))));
     g.finish();
 }
 
 fn bench_p1_cpp(c: &mut Criterion) {
+    let input = read_input();
     let mut g = c.benchmark_group("criterion");
     g.bench_function("part1_cpp", |b| {
-        b.iter_batched(
-            || {
-                let mut f = File::open("input.txt").expect("can't open file");
-                let mut buf = String::new();
-                f.read_to_string(&mut buf).expect("can't read file");
-                buf
-            },
-            |f| day10::day10cpp::p1_cpp(&f),
-            BatchSize::SmallInput,
-        )
+        b.iter(|| day10::day10cpp::p1_cpp(black_box(&input)))
     });
     g.finish();
 }
 
 #[cfg(target_os = "macos")]
 fn bench_p1_swift(c: &mut Criterion) {
+    let input = read_input();
     let mut g = c.benchmark_group("criterion");
     g.bench_function("part1_swift", |b| {
-        b.iter_batched(
-            || {
-                let mut f = File::open("input.txt").expect("can't open file");
-                let mut buf = String::new();
-                f.read_to_string(&mut buf).expect("can't read file");
-                buf
-            },
-            |f|  day10::day10swift::p1_swift(&f),
-            BatchSize::SmallInput,
-        )
+        b.iter(|| day10::day10swift::p1_swift(black_box(&input)))
     });
     g.finish();
 }
 
 fn bench_p2(c: &mut Criterion) {
+    let input = read_input();
     let mut g = c.benchmark_group("criterion");
-    g.bench_function("part2_rust", |b| {
-        b.iter_batched(
-            || {
-                let mut f = File::open("input.txt").expect("can't open file");
-                let mut buf = String::new();
-                f.read_to_string(&mut buf).expect("can't read file");
-                buf
-            },
-            |f| p2(&f),
-            BatchSize::SmallInput,
-        )
+    g.bench_function("part2_rust", |b| b.iter(|| p2(black_box(&input))));
+    g.finish()
+}
+fn bench_p2_cpp(c: &mut Criterion) {
+    let input = read_input();
+    let mut g = c.benchmark_group("criterion");
+    g.bench_function("part2_cpp", |b| {
+        b.iter(|| day10::day10cpp::p2_cpp(black_box(&input)))
     });
     g.finish()
 }
+
+fn bench_p2_scanline(c: &mut Criterion) {
+    let input = read_input();
+    let mut g = c.benchmark_group("criterion");
+    g.bench_function("part2_rust_scanline", |b| {
+        b.iter(|| p2_with(black_box(&input), Strategy::Scanline))
+    });
+    g.finish()
+}
+
+fn bench_shoelace(c: &mut Criterion) {
+    let input = read_input();
+    let map = PipeMap::new(&input).expect("can't parse file");
+    let mut g = c.benchmark_group("criterion");
+    g.bench_function("shoelace_rust", |b| b.iter(|| map.enclosed_count()));
+    let path = map.loop_path().expect("no loop in file");
+    g.bench_function("shoelace_rust_unchecked", |b| {
+        b.iter(|| common::shoelace_unchecked(black_box(path)))
+    });
+    g.finish()
+}
+
 #[cfg(target_os = "macos")]
 fn bench_p2_swift(c: &mut Criterion) {
+    let input = read_input();
     let mut g = c.benchmark_group("criterion");
     g.bench_function("part2_swift", |b| {
-        b.iter_batched(
-            || {
-                let mut f = File::open("input.txt").expect("can't open file");
-                let mut buf = String::new();
-                f.read_to_string(&mut buf).expect("can't read file");
-                buf
-            },
-            |f| day10::day10swift::p2_swift(&f),
-            BatchSize::SmallInput,
-        )
+        b.iter(|| day10::day10swift::p2_swift(black_box(&input)))
     });
     g.finish()
 }
 
+/// A solution of one of the parts, with the answer as u64.
+type Solver = fn(&str) -> u64;
+
+/// The `scaling` benchmarks, see [`common::SIZES`].
+fn bench_scaling(c: &mut Criterion) {
+    #[allow(unused_mut)]
+    let mut solvers: Vec<(&str, Solver)> = vec![
+        ("part1_rust", |input| p1(input) as u64),
+        ("part1_cpp", day10::day10cpp::p1_cpp),
+        ("part2_rust", |input| p2(input) as u64),
+        ("part2_cpp", day10::day10cpp::p2_cpp),
+    ];
+    #[cfg(target_os = "macos")]
+    solvers.extend([
+        ("part1_swift", day10::day10swift::p1_swift as Solver),
+        ("part2_swift", day10::day10swift::p2_swift),
+    ]);
+    for (name, solver) in solvers {
+        let mut g = c.benchmark_group(format!("scaling/{name}"));
+        g.sample_size(10);
+        for size in common::SIZES {
+            g.throughput(Throughput::Elements((size * size) as u64));
+            g.bench_with_input(
+                BenchmarkId::from_parameter(format!("{size}x{size}")),
+                common::generated(size),
+                |b, input| b.iter(|| solver(black_box(input))),
+            );
+        }
+        g.finish();
+    }
+}
+
 #[cfg(target_os = "macos")]
-criterion_group!(benches, bench_p1, bench_p1_swift, bench_p1_cpp, bench_p2, bench_p2_swift);
+criterion_group!(
+    benches,
+    bench_p1,
+    bench_p1_swift,
+    bench_p1_cpp,
+    bench_p2,
+    bench_p2_cpp,
+    bench_p2_swift,
+    bench_p2_scanline,
+    bench_shoelace,
+    bench_scaling
+);
 
 #[cfg(not(target_os = "macos"))]
-criterion_group!(benches, bench_p1, bench_p1_cpp, bench_p2);
+criterion_group!(
+    benches,
+    bench_p1,
+    bench_p1_cpp,
+    bench_p2,
+    bench_p2_cpp,
+    bench_p2_scanline,
+    bench_shoelace,
+    bench_scaling
+);
 
 criterion_main!(benches);
@@ -1,4 +1,6 @@
-use day10::{p1, p2};
+use day10::{p1, p2, p2_checked, p2_flood, p2_with, PipeMap, Strategy};
+
+mod common;
 
 fn main() {
     // Run registered benchmarks.
@@ -14,7 +16,7 @@ fn part1_rust() {
 #[divan::bench]
 fn part1_swift() {
     let input = divan::black_box(include_str!("../input.txt",));
-     day10::day10swift::p1_swift(input);
+    day10::day10swift::p1_swift(input);
 }
 
 #[divan::bench]
@@ -28,7 +30,94 @@ fn part2_rust() {
     p2(divan::black_box(include_str!("../input.txt",)));
 }
 
+#[divan::bench]
+fn shoelace_rust(bencher: divan::Bencher) {
+    let map = PipeMap::new(include_str!("../input.txt",)).unwrap();
+    bencher.bench_local(|| divan::black_box(&map).enclosed_count());
+}
+
+#[divan::bench]
+fn shoelace_rust_unchecked(bencher: divan::Bencher) {
+    let map = PipeMap::new(include_str!("../input.txt",)).unwrap();
+    let path = map.loop_path().unwrap();
+    bencher.bench_local(|| common::shoelace_unchecked(divan::black_box(path)));
+}
+
+#[divan::bench]
+fn part2_rust_checked() {
+    p2_checked(divan::black_box(include_str!("../input.txt",))).unwrap();
+}
+
+#[divan::bench]
+fn part2_rust_flood() {
+    p2_flood(divan::black_box(include_str!("../input.txt",)));
+}
+
+#[divan::bench]
+fn part2_rust_scanline() {
+    p2_with(
+        divan::black_box(include_str!("../input.txt",)),
+        Strategy::Scanline,
+    );
+}
+
+#[divan::bench]
+fn part2_cpp() {
+    let input = divan::black_box(include_str!("../input.txt",));
+    day10::day10cpp::p2_cpp(input);
+}
+
+#[cfg(target_os = "macos")]
 #[divan::bench]
 fn part2_swift() {
-    day10::day10swift::p2_swift(divan::black_box(include_str!("../input.txt",)));
-}
\ No newline at end of file
+    day10::day10swift::p2_swift(divan::black_box(
+        include_str!("../input.txt",),
+    ));
+}
+
+/// The `scaling` benchmarks, see [`common::SIZES`].
+mod scaling {
+    use day10::{p1, p2};
+    use divan::{counter::ItemsCount, Bencher};
+
+    use crate::common::{generated, SIZES};
+
+    fn bench(bencher: Bencher, size: usize, solver: impl Fn(&str) -> u64) {
+        let input = generated(size);
+        bencher
+            .counter(ItemsCount::new(size * size))
+            .bench_local(|| solver(divan::black_box(input)));
+    }
+
+    #[divan::bench(args = SIZES, sample_count = 10)]
+    fn part1_rust(bencher: Bencher, size: usize) {
+        bench(bencher, size, |input| p1(input) as u64);
+    }
+
+    #[cfg(target_os = "macos")]
+    #[divan::bench(args = SIZES, sample_count = 10)]
+    fn part1_swift(bencher: Bencher, size: usize) {
+        bench(bencher, size, day10::day10swift::p1_swift);
+    }
+
+    #[divan::bench(args = SIZES, sample_count = 10)]
+    fn part1_cpp(bencher: Bencher, size: usize) {
+        bench(bencher, size, day10::day10cpp::p1_cpp);
+    }
+
+    #[divan::bench(args = SIZES, sample_count = 10)]
+    fn part2_rust(bencher: Bencher, size: usize) {
+        bench(bencher, size, |input| p2(input) as u64);
+    }
+
+    #[divan::bench(args = SIZES, sample_count = 10)]
+    fn part2_cpp(bencher: Bencher, size: usize) {
+        bench(bencher, size, day10::day10cpp::p2_cpp);
+    }
+
+    #[cfg(target_os = "macos")]
+    #[divan::bench(args = SIZES, sample_count = 10)]
+    fn part2_swift(bencher: Bencher, size: usize) {
+        bench(bencher, size, day10::day10swift::p2_swift);
+    }
+}
@@ -13,9 +13,21 @@ fn main() {
             .link();
     }
 
-    let dst = Config::new(".")
-        .define("CMAKE_EXPORT_COMPILE_COMMANDS", "YES")
-        .build();
+    let mut config = Config::new(".");
+    config.define("CMAKE_EXPORT_COMPILE_COMMANDS", "YES");
+    let sanitize = std::env::var_os("CARGO_FEATURE_SANITIZE").is_some();
+    let linux = std::env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("linux");
+    if sanitize && linux {
+        config.define("DAY10_SANITIZE", "ON");
+    } else if sanitize {
+        println!("cargo:warning=the sanitize feature only works on Linux");
+    }
+    let dst = config.build();
     println!("cargo:rustc-link-search=native={}", dst.display());
     println!("cargo:rustc-link-lib=static=day10cpp");
+    if sanitize && linux {
+        // the runtimes of the sanitizers the C++ code is compiled with
+        println!("cargo:rustc-link-lib=dylib=asan");
+        println!("cargo:rustc-link-lib=dylib=ubsan");
+    }
 }
@@ -37,7 +37,10 @@ public:
         return Location(m_x, m_y - 1);
     }
 
-    std::optional<Location> south() const {
+    std::optional<Location> south(size_t height) const {
+        if (m_y + 1 >= height) {
+            return std::nullopt;
+        }
         return Location(m_x, m_y + 1);
     }
 
@@ -48,7 +51,10 @@ public:
         return Location(m_x - 1, m_y);
     }
 
-    std::optional<Location> east() const {
+    std::optional<Location> east(size_t width) const {
+        if (m_x + 1 >= width) {
+            return std::nullopt;
+        }
         return Location(m_x + 1, m_y);
     }
 };
@@ -66,10 +72,6 @@ class Map {
         : m_lines(lines), m_width(width), m_start(start),
           m_height(lines.size()) {}
 
-    Map(const Map &other)
-        : m_lines(other.m_lines), m_width(other.m_width),
-          m_start(other.m_start), m_height(other.m_height) {}
-
     // symbols on the tiles connect them to each other
     // | connects up and down
     // - connects left and right
@@ -91,21 +93,21 @@ class Map {
         switch (tile) {
         case '|':
             if (coming_from == NORTH) {
-                next = std::make_tuple(current.south(), NORTH);
+                next = std::make_tuple(current.south(m_height), NORTH);
             } else if (coming_from == SOUTH) {
                 next = std::make_tuple(current.north(), SOUTH);
             }
             break;
         case '-':
             if (coming_from == WEST) {
-                next = std::make_tuple(current.east(), WEST);
+                next = std::make_tuple(current.east(m_width), WEST);
             } else if (coming_from == EAST) {
                 next = std::make_tuple(current.west(), EAST);
             }
             break;
         case 'L':
             if (coming_from == NORTH) {
-                next = std::make_tuple(current.east(), WEST);
+                next = std::make_tuple(current.east(m_width), WEST);
             } else if (coming_from == EAST) {
                 next = std::make_tuple(current.north(), SOUTH);
             }
@@ -121,14 +123,14 @@ class Map {
             if (coming_from == SOUTH) {
                 next = std::make_tuple(current.west(), EAST);
             } else if (coming_from == WEST) {
-                next = std::make_tuple(current.south(), NORTH);
+                next = std::make_tuple(current.south(m_height), NORTH);
             }
             break;
         case 'F':
             if (coming_from == SOUTH) {
-                next = std::make_tuple(current.east(), WEST);
+                next = std::make_tuple(current.east(m_width), WEST);
             } else if (coming_from == EAST) {
-                next = std::make_tuple(current.south(), NORTH);
+                next = std::make_tuple(current.south(m_height), NORTH);
             }
             break;
         }
@@ -143,11 +145,6 @@ class Map {
             return std::nullopt;
         }
 
-        if (next_location.value().x >= m_width ||
-                next_location.value().y >= m_height) {
-            return std::nullopt;
-        }
-
         // check wether next is a tile not connected to the direction we are
         // coming from, return None, if it is (S connects to everything)
         switch (direction) {
@@ -176,8 +173,11 @@ class Map {
         return std::make_tuple(next_location.value(), direction);
     }
 
-    inline const char &get_tile(const Location &next_location) const {
-        return m_lines[next_location.y][next_location.x];
+    // Rows shorter than the first one continue with ground, like in the
+    // Rust solution.
+    inline char get_tile(const Location &location) const {
+        const auto &line = m_lines[location.y];
+        return location.x < line.size() ? line[location.x] : '.';
     }
 
     std::vector<std::tuple<Location, Direction>> connected_to() const {
@@ -188,7 +188,7 @@ class Map {
                 connected.push_back(std::make_tuple(north.value(), SOUTH));
             }
         }
-        auto south = m_start.south();
+        auto south = m_start.south(m_height);
         if (south.has_value()) {
             if (std::strchr("|LJ", get_tile(south.value())) != nullptr) {
                 connected.push_back(std::make_tuple(south.value(), NORTH));
@@ -200,7 +200,7 @@ class Map {
                 connected.push_back(std::make_tuple(west.value(), EAST));
             }
         }
-        auto east = m_start.east();
+        auto east = m_start.east(m_width);
         if (east.has_value()) {
             if (std::strchr("-7J", get_tile(east.value())) != nullptr) {
                 connected.push_back(std::make_tuple(east.value(), WEST));
@@ -221,20 +221,29 @@ class Map {
     }
 
 public:
-    static Map parse(const std::span<const char> &input) {
-        // It is really hard in c++ to structure the input
-        // without copying it. Let's ask Copilot for help.
-        // Create a vector of spans for each line.
-        size_t running_width = SIZE_MAX;
+    Map(const Map &other)
+        : m_lines(other.m_lines), m_width(other.m_width),
+          m_start(other.m_start), m_height(other.m_height) {}
+
+    // Split the input into lines, without the '\r' of Windows line endings,
+    // the same way Rust's str::lines() does. The map is as wide as its first
+    // line. Input without lines or without a starting tile is rejected with
+    // std::nullopt.
+    static std::optional<Map> parse(const std::span<const char> &input) {
         std::vector<std::span<const char>> lines;
         std::optional<Location> start_location = std::nullopt;
         size_t start_of_current_line = 0;
-        char *start_of_line_ptr = (char *) input.data();
-        char *end_of_line_ptr = (char *) memchr(input.data(), '\n', input.size());
-        while (end_of_line_ptr != nullptr) {
-            size_t i = end_of_line_ptr - start_of_line_ptr;
-            auto line = input.subspan(start_of_current_line,
-                                      i);
+        while (start_of_current_line < input.size()) {
+            auto rest = input.subspan(start_of_current_line);
+            const char *end_of_line_ptr =
+                (const char *) memchr(rest.data(), '\n', rest.size());
+            size_t i = end_of_line_ptr != nullptr
+                           ? end_of_line_ptr - rest.data()
+                           : rest.size();
+            auto line = rest.first(i);
+            if (!line.empty() && line.back() == '\r') {
+                line = line.first(line.size() - 1);
+            }
 
             if (!start_location.has_value()) {
                 start_location = look_for_startposition(line, lines);
@@ -242,24 +251,12 @@ public:
 
             lines.push_back(line);
             start_of_current_line = start_of_current_line + i + 1;
-            start_of_line_ptr = end_of_line_ptr + 1;
-            end_of_line_ptr = (char *) memchr(start_of_line_ptr, '\n',
-                                              input.size() - (end_of_line_ptr - input.data()));
-
-            running_width = std::min(
-                                running_width, line.size()); // don't count the newline
         }
-        auto i = input.size() - start_of_current_line;
-        if (i > 0) // last line does not end with newline (or is empty)
-        {
-            auto line = input.subspan(start_of_current_line, i);
-            if (!start_location.has_value()) {
-                start_location = look_for_startposition(line, lines);
-            }
-            lines.push_back(line);
+        if (lines.empty() || !start_location.has_value()) {
+            return std::nullopt;
         }
-        return Map(lines, running_width,
-                   start_location.value_or(Location(0, 0)));
+        size_t width = lines.front().size();
+        return Map(lines, width, start_location.value());
     }
 
     std::optional<std::vector<Location>> find_loop() const {
@@ -284,17 +281,56 @@ public:
 };
 
 size_t p1(const std::span<const char> &input) {
-    Map map = Map::parse(input);
-    auto loop = map.find_loop();
+    auto map = Map::parse(input);
+    if (!map.has_value()) {
+        return 0;
+    }
+    auto loop = map.value().find_loop();
     if (loop.has_value()) {
         return loop.value().size() / 2;
     }
     return 0;
 }
 
+// Computes the number of enclosed tiles of the given loop. Unlike the Rust
+// version, the path returned by find_loop() does not end with the starting
+// point again, so the edge back to it is added after the others.
+size_t shoelace_with_picks_theorem(const std::vector<Location> &path) {
+    size_t n = path.size();
+    // shoelace for area, in signed arithmetic as area may become negative
+    ptrdiff_t area = 0;
+    for (size_t i = 0; i < n; i++) {
+        const Location &current = path[i];
+        const Location &next = i + 1 < n ? path[i + 1] : path[0];
+        area += static_cast<ptrdiff_t>(current.y + next.y) *
+                (static_cast<ptrdiff_t>(current.x) -
+                 static_cast<ptrdiff_t>(next.x));
+    }
+    size_t abs_area = static_cast<size_t>(area < 0 ? -area : area) / 2;
+    // Pick's theorem
+    return abs_area + 1 - n / 2;
+}
+
+size_t p2(const std::span<const char> &input) {
+    auto map = Map::parse(input);
+    if (!map.has_value()) {
+        return 0;
+    }
+    auto loop = map.value().find_loop();
+    if (loop.has_value()) {
+        return shoelace_with_picks_theorem(loop.value());
+    }
+    return 0;
+}
+
 extern "C" {
     size_t run_p1_cpp(const char *input, size_t input_len) {
         auto span = std::span(input, input_len);
         return p1(span);
     }
+
+    size_t run_p2_cpp(const char *input, size_t input_len) {
+        auto span = std::span(input, input_len);
+        return p2(span);
+    }
 }
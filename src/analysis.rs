@@ -0,0 +1,461 @@
+use memchr::memchr;
+
+use crate::map::{shoelace_sum, shoelace_with_picks_theorem};
+use crate::{Location, PipeMap};
+
+/// A closed loop of pipes somewhere in a map.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PipeLoop {
+    /// The tiles of the loop. Like [`PipeMap::loop_path`], it starts and
+    /// ends with the same tile, which is the top left one of the loop
+    /// unless the loop runs through `S`.
+    pub path: Vec<Location>,
+    /// The smallest row and column of the tiles of the loop.
+    pub top_left: Location,
+    /// The largest row and column of the tiles of the loop.
+    pub bottom_right: Location,
+    /// The number of tiles enclosed by the loop.
+    pub enclosed: usize,
+}
+
+impl PipeLoop {
+    fn new(path: Vec<Location>) -> PipeLoop {
+        let mut top_left = path[0];
+        let mut bottom_right = path[0];
+        for &loc in &path {
+            extend_bounds(&mut top_left, &mut bottom_right, loc);
+        }
+        let enclosed = shoelace_with_picks_theorem(&path);
+        PipeLoop {
+            path,
+            top_left,
+            bottom_right,
+            enclosed,
+        }
+    }
+
+    /// The number of tiles of the loop.
+    pub fn length(&self) -> usize {
+        self.path.len() - 1
+    }
+}
+
+/// Find all closed loops of pipes in the map, including the decoys that
+/// are not connected to `S`. The loop through `S` comes first if there is
+/// one, the others follow in the order of their top left tiles. This takes
+/// time linear in the size of the map.
+pub fn closed_loops(map: &PipeMap) -> Vec<PipeLoop> {
+    map.find_all_loops()
+        .into_iter()
+        .map(PipeLoop::new)
+        .collect()
+}
+
+/// The direction in which a loop runs, as the map is printed with its
+/// rows going down.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Orientation {
+    /// The inside of the loop is on the right hand side of every step.
+    Clockwise,
+    /// The inside of the loop is on the left hand side of every step.
+    CounterClockwise,
+}
+
+/// Find the direction in which the closed path runs, like the path of a
+/// [`PipeLoop`] or [`PipeMap::loop_path`]. None if the path encloses no
+/// area, which is never the case for a loop of pipes.
+pub fn orientation(path: &[Location]) -> Option<Orientation> {
+    match shoelace_sum(path).signum() {
+        1 => Some(Orientation::Clockwise),
+        -1 => Some(Orientation::CounterClockwise),
+        _ => None,
+    }
+}
+
+/// How often a loop changes its direction.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub struct TurnCounts {
+    /// Tiles where the loop turns left, when following the path.
+    pub left: usize,
+    /// Tiles where the loop turns right, when following the path.
+    pub right: usize,
+    /// Tiles where the loop goes straight on.
+    pub straight: usize,
+}
+
+/// Count the turns along the closed path, like the path of a [`PipeLoop`]
+/// or [`PipeMap::loop_path`], including the turn at its first tile. For a
+/// loop of pipes, there are four more turns towards its inside than away
+/// from it.
+pub fn turns(path: &[Location]) -> TurnCounts {
+    let mut counts = TurnCounts::default();
+    // without the closing tile, which is the same as the first one
+    let tiles = &path[..path.len().saturating_sub(1)];
+    let step = |from: Location, to: Location| {
+        (
+            to.x as isize - from.x as isize,
+            to.y as isize - from.y as isize,
+        )
+    };
+    for (i, &loc) in tiles.iter().enumerate() {
+        let before = tiles[(i + tiles.len() - 1) % tiles.len()];
+        let after = tiles[(i + 1) % tiles.len()];
+        let (dx_in, dy_in) = step(before, loc);
+        let (dx_out, dy_out) = step(loc, after);
+        // with the rows going down, a positive cross product turns right
+        match (dx_in * dy_out - dy_in * dx_out).signum() {
+            1 => counts.right += 1,
+            -1 => counts.left += 1,
+            _ => counts.straight += 1,
+        }
+    }
+    counts
+}
+
+/// How the pipes of a [`Component`] are connected.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum ComponentKind {
+    /// Every pipe is connected to two others, the pipes form a loop.
+    Cycle,
+    /// The pipes form a line between two dead ends, ordered by row and
+    /// column. Both ends are the same tile for a pipe that is not
+    /// connected at all.
+    Chain { ends: [Location; 2] },
+    /// `S` is connected to more than two pipes, so there is no single way
+    /// through it.
+    Branching,
+}
+
+/// A summary of a set of pipes that are connected to each other, but not
+/// to any other pipe of the map.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Component {
+    pub kind: ComponentKind,
+    /// The number of tiles of the component.
+    pub size: usize,
+    /// The smallest row and column of the tiles of the component.
+    pub top_left: Location,
+    /// The largest row and column of the tiles of the component.
+    pub bottom_right: Location,
+    /// Whether `S` is one of the tiles of the component.
+    pub contains_start: bool,
+}
+
+/// Split all pipes of the map, including `S`, into connected components.
+/// Two neighbouring pipes are connected if they lead into each other, like
+/// the pipes of the loop do, and `S` connects to every pipe leading into
+/// it. The components are in the order of their top left tiles.
+pub fn components(map: &PipeMap) -> Vec<Component> {
+    let width = map.width();
+    let mut seen = vec![false; width * map.height()];
+    let mut result = Vec::new();
+    let mut stack = Vec::new();
+    for y in 0..map.height() {
+        for x in 0..width {
+            let first = Location { x, y };
+            let is_pipe = map
+                .tile(first)
+                .is_some_and(|tile| memchr(tile, b"S|-LJ7F").is_some());
+            if seen[y * width + x] || !is_pipe {
+                continue;
+            }
+            seen[y * width + x] = true;
+            stack.push(first);
+            let mut component = Component {
+                kind: ComponentKind::Cycle,
+                size: 0,
+                top_left: first,
+                bottom_right: first,
+                contains_start: false,
+            };
+            let mut ends = Vec::new();
+            while let Some(loc) = stack.pop() {
+                component.size += 1;
+                component.contains_start |= loc == map.start();
+                extend_bounds(
+                    &mut component.top_left,
+                    &mut component.bottom_right,
+                    loc,
+                );
+                let neighbours = map.pipe_neighbours(loc);
+                match neighbours.len() {
+                    0 => ends.extend([loc, loc]),
+                    1 => ends.push(loc),
+                    2 => {}
+                    _ => component.kind = ComponentKind::Branching,
+                }
+                for neighbour in neighbours {
+                    let i = neighbour.y * width + neighbour.x;
+                    if !seen[i] {
+                        seen[i] = true;
+                        stack.push(neighbour);
+                    }
+                }
+            }
+            if let ([a, b], ComponentKind::Cycle) =
+                (ends.as_slice(), component.kind)
+            {
+                let mut ends = [*a, *b];
+                ends.sort_by_key(|loc| (loc.y, loc.x));
+                component.kind = ComponentKind::Chain { ends };
+            }
+            result.push(component);
+        }
+    }
+    result
+}
+
+/// Widen the bounding box given by its corners to include the location.
+fn extend_bounds(
+    top_left: &mut Location,
+    bottom_right: &mut Location,
+    loc: Location,
+) {
+    top_left.x = top_left.x.min(loc.x);
+    top_left.y = top_left.y.min(loc.y);
+    bottom_right.x = bottom_right.x.max(loc.x);
+    bottom_right.y = bottom_right.y.max(loc.y);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::read_input;
+
+    #[test]
+    fn test_closed_loops() {
+        let map = PipeMap::new(
+            "F7...\n\
+             LJ.F7\n\
+             .S7||\n\
+             .LJLJ\n\
+             F-7F-\n\
+             L-J|.\n",
+        )
+        .unwrap();
+        let loops = closed_loops(&map);
+        assert_eq!(loops.len(), 4);
+        assert_eq!(loops[0].path.first(), Some(&map.start()));
+        assert_eq!(loops[0].length(), 4);
+        assert_eq!(loops[0].enclosed, 0);
+        let top_lefts = loops.iter().map(|l| l.top_left).collect::<Vec<_>>();
+        assert_eq!(
+            top_lefts,
+            vec![
+                Location { x: 1, y: 2 },
+                Location { x: 0, y: 0 },
+                Location { x: 3, y: 1 },
+                Location { x: 0, y: 4 },
+            ]
+        );
+        assert_eq!(loops[2].bottom_right, Location { x: 4, y: 3 });
+        assert_eq!(loops[2].length(), 6);
+        assert_eq!(loops[3].path.first(), Some(&Location { x: 0, y: 4 }));
+        assert_eq!(loops[3].path.last(), Some(&Location { x: 0, y: 4 }));
+        assert_eq!(loops[3].length(), 6);
+    }
+
+    #[test]
+    fn test_closed_loops_enclosed() {
+        let map = PipeMap::new(
+            "S-7......\n\
+             L-J.F--7.\n\
+             ....|..|.\n\
+             ....L--J.\n",
+        )
+        .unwrap();
+        let loops = closed_loops(&map);
+        assert_eq!(loops.len(), 2);
+        assert_eq!(loops[0].length(), 6);
+        assert_eq!(loops[0].enclosed, 0);
+        assert_eq!(loops[1].top_left, Location { x: 4, y: 1 });
+        assert_eq!(loops[1].bottom_right, Location { x: 7, y: 3 });
+        assert_eq!(loops[1].length(), 10);
+        assert_eq!(loops[1].enclosed, 2);
+    }
+
+    #[test]
+    fn test_closed_loops_without_start_loop() {
+        let map = PipeMap::new("S-7F7\n..|LJ\n").unwrap();
+        let loops = closed_loops(&map);
+        assert_eq!(loops.len(), 1);
+        assert_eq!(loops[0].top_left, Location { x: 3, y: 0 });
+        assert_eq!(loops[0].enclosed, 0);
+    }
+
+    #[test]
+    fn test_closed_loops_input() {
+        let buf = read_input();
+        let map = PipeMap::new(&buf).unwrap();
+        let loops = closed_loops(&map);
+        assert_eq!(loops[0].length(), 13556);
+        assert_eq!(loops[0].enclosed, 433);
+        let mut on_loops = vec![false; map.width() * map.height()];
+        for pipe_loop in &loops {
+            for loc in &pipe_loop.path[1..] {
+                let i = loc.y * map.width() + loc.x;
+                assert!(!on_loops[i], "{loc:?} is on two loops");
+                on_loops[i] = true;
+            }
+        }
+        for pipe_loop in &loops[1..] {
+            assert_eq!(
+                Some(pipe_loop.top_left),
+                pipe_loop.path.first().copied()
+            );
+            assert_eq!(map.tile(pipe_loop.top_left), Some(b'F'));
+        }
+    }
+
+    #[test]
+    fn test_orientation_and_turns() {
+        let map = PipeMap::new(".....\n.S-7.\n.|.|.\n.L-J.\n.....\n").unwrap();
+        // the loop leaves S to the south
+        let path = map.loop_path().unwrap();
+        assert_eq!(path[1], Location { x: 1, y: 2 });
+        assert_eq!(orientation(path), Some(Orientation::CounterClockwise));
+        let counts = turns(path);
+        assert_eq!(
+            counts,
+            TurnCounts {
+                left: 4,
+                right: 0,
+                straight: 4
+            }
+        );
+        let reversed = path.iter().rev().copied().collect::<Vec<_>>();
+        assert_eq!(orientation(&reversed), Some(Orientation::Clockwise));
+        assert_eq!(turns(&reversed).right, 4);
+        assert_eq!(orientation(&[]), None);
+        assert_eq!(turns(&[]), TurnCounts::default());
+    }
+
+    #[test]
+    fn test_turns_input() {
+        let buf = read_input();
+        let map = PipeMap::new(&buf).unwrap();
+        let path = map.loop_path().unwrap();
+        let counts = turns(path);
+        assert_eq!(counts.left + counts.right + counts.straight, 13556);
+        let corners = path[1..]
+            .iter()
+            .filter(|&&loc| {
+                let tile = map.tile(loc).unwrap();
+                let tile = if tile == b'S' {
+                    map.start_tile().unwrap()
+                } else {
+                    tile
+                };
+                b"LJ7F".contains(&tile)
+            })
+            .count();
+        assert_eq!(counts.left + counts.right, corners);
+        match orientation(path) {
+            Some(Orientation::Clockwise) => {
+                assert_eq!(counts.right, counts.left + 4)
+            }
+            Some(Orientation::CounterClockwise) => {
+                assert_eq!(counts.left, counts.right + 4)
+            }
+            None => panic!("the loop encloses no area"),
+        }
+        for pipe_loop in closed_loops(&map) {
+            let counts = turns(&pipe_loop.path);
+            assert_eq!(counts.left.abs_diff(counts.right), 4);
+        }
+    }
+
+    #[test]
+    fn test_components() {
+        let map = PipeMap::new(
+            "S-7.F-\n\
+             |.|.|.\n\
+             L-J.|7\n\
+             F7..L-\n",
+        )
+        .unwrap();
+        let components = components(&map);
+        assert_eq!(
+            components,
+            vec![
+                Component {
+                    kind: ComponentKind::Cycle,
+                    size: 8,
+                    top_left: Location { x: 0, y: 0 },
+                    bottom_right: Location { x: 2, y: 2 },
+                    contains_start: true,
+                },
+                Component {
+                    kind: ComponentKind::Chain {
+                        ends: [
+                            Location { x: 5, y: 0 },
+                            Location { x: 5, y: 3 }
+                        ]
+                    },
+                    size: 6,
+                    top_left: Location { x: 4, y: 0 },
+                    bottom_right: Location { x: 5, y: 3 },
+                    contains_start: false,
+                },
+                Component {
+                    kind: ComponentKind::Chain {
+                        ends: [
+                            Location { x: 5, y: 2 },
+                            Location { x: 5, y: 2 }
+                        ]
+                    },
+                    size: 1,
+                    top_left: Location { x: 5, y: 2 },
+                    bottom_right: Location { x: 5, y: 2 },
+                    contains_start: false,
+                },
+                Component {
+                    kind: ComponentKind::Chain {
+                        ends: [
+                            Location { x: 0, y: 3 },
+                            Location { x: 1, y: 3 }
+                        ]
+                    },
+                    size: 2,
+                    top_left: Location { x: 0, y: 3 },
+                    bottom_right: Location { x: 1, y: 3 },
+                    contains_start: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_components_branching() {
+        let map = PipeMap::new(".|.\n-S-\n.|.\n").unwrap();
+        let components = components(&map);
+        assert_eq!(components.len(), 1);
+        assert_eq!(components[0].kind, ComponentKind::Branching);
+        assert_eq!(components[0].size, 5);
+        assert!(components[0].contains_start);
+    }
+
+    #[test]
+    fn test_components_input() {
+        let buf = read_input();
+        let map = PipeMap::new(&buf).unwrap();
+        let components = components(&map);
+        let pipes = buf.bytes().filter(|b| b"S|-LJ7F".contains(b)).count();
+        assert_eq!(components.iter().map(|c| c.size).sum::<usize>(), pipes);
+        let mut cycles = components
+            .iter()
+            .filter(|c| c.kind == ComponentKind::Cycle)
+            .map(|c| (c.top_left, c.bottom_right, c.size))
+            .collect::<Vec<_>>();
+        let mut loops = closed_loops(&map)
+            .iter()
+            .map(|l| (l.top_left, l.bottom_right, l.length()))
+            .collect::<Vec<_>>();
+        cycles.sort_by_key(|&(loc, _, _)| (loc.y, loc.x));
+        loops.sort_by_key(|&(loc, _, _)| (loc.y, loc.x));
+        assert_eq!(cycles, loops);
+        assert!(components
+            .iter()
+            .all(|c| c.kind != ComponentKind::Branching));
+    }
+}
@@ -3,17 +3,24 @@ extern crate link_cplusplus;
 #[link(name = "day10cpp", kind = "static")]
 extern "C" {
     fn run_p1_cpp(bytes: *const u8, size: usize) -> u64;
+    fn run_p2_cpp(bytes: *const u8, size: usize) -> u64;
 }
 
+/// Part 1 by the C++ solution. Like [`crate::p1`], it is 0 if there is no
+/// loop, and also if the input is not a rectangle of tiles with an `S`.
 pub fn p1_cpp(input: &str) -> u64 {
     unsafe { run_p1_cpp(input.as_ptr(), input.len()) }
 }
 
+/// Part 2 by the C++ solution, 0 in the same cases as [`p1_cpp`].
+pub fn p2_cpp(input: &str) -> u64 {
+    unsafe { run_p2_cpp(input.as_ptr(), input.len()) }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::{fs::File, io::Read};
-
-    use crate::day10cpp::p1_cpp;
+    use crate::day10cpp::{p1_cpp, p2_cpp};
+    use crate::tests::read_input;
 
     #[test]
     fn test_cpp_sample() {
@@ -34,10 +41,74 @@ L7JLJL-JLJLJL--JLJ.L
 
     #[test]
     fn test_cpp_part1() {
-        let mut f = File::open("input.txt").expect("can't open file");
-        let mut buf = String::new();
-        f.read_to_string(&mut buf).expect("can't read file");
+        let buf = read_input();
         let result = p1_cpp(&buf);
         assert_eq!(result, 6778);
     }
+
+    #[test]
+    fn test_cpp_p2_sample() {
+        let input = "FF7FSF7F7F7F7F7F---7
+L|LJ||||||||||||F--J
+FL-7LJLJ||||||LJL-77
+F--JF--7||LJLJIF7FJ-
+L---JF-JLJIIIIFJLJJ7
+|F|F-JF---7IIIL7L|7|
+|FFJF7L7F-JF7IIL---7
+7-L-JL7||F7|L7F-7F7|
+L.L7LFJ|||||FJL7||LJ
+L7JLJL-JLJLJL--JLJ.L
+";
+        let result = p2_cpp(input);
+        assert_eq!(result, 10);
+    }
+
+    #[test]
+    fn test_cpp_part2() {
+        let buf = read_input();
+        let result = p2_cpp(&buf);
+        assert_eq!(result, 433);
+    }
+
+    #[test]
+    fn test_cpp_start_at_borders() {
+        // S in the bottom row and the rightmost column
+        assert_eq!(p1_cpp("F-7\n|.|\nL-S\n"), 4);
+        assert_eq!(p2_cpp("F-7\n|.|\nL-S\n"), 1);
+        assert_eq!(p1_cpp("F-7\n|.|\nL-S"), 4);
+        assert_eq!(p1_cpp("..F-S\n..L-J\n"), 3);
+        assert_eq!(p1_cpp("S\n"), 0);
+    }
+
+    #[test]
+    fn test_cpp_malformed() {
+        assert_eq!(p1_cpp(""), 0);
+        assert_eq!(p1_cpp("\n\n"), 0);
+        assert_eq!(p2_cpp("F-7\n|.|\nL-J\n"), 0);
+    }
+
+    #[test]
+    fn test_cpp_ragged() {
+        // rows are read like the Rust solution reads them, short rows
+        // continue with ground and a trailing blank line is ignored
+        for input in [
+            "S-7\n|.|\nL-J.\n",
+            "S-7\n|.\nL-J\n",
+            "F-7\n|.|\nL-S\n\n",
+            ".....\n.S-7.\n.|.|.\n.L-J.\n.....        \n",
+        ] {
+            assert_eq!(p1_cpp(input), crate::p1(input) as u64, "{input}");
+            assert_eq!(p2_cpp(input), crate::p2(input) as u64, "{input}");
+        }
+        assert_eq!(p1_cpp("S-7\n|.|\nL-J.\n"), 4);
+        assert_eq!(p1_cpp("S-7\n|.\nL-J\n"), 0);
+    }
+
+    #[test]
+    fn test_cpp_crlf() {
+        let input = "F-7\r\n|.|\r\nL-S\r\n";
+        assert_eq!(p1_cpp(input), 4);
+        assert_eq!(p2_cpp(input), 1);
+        assert_eq!(p1_cpp("F-7\r\n|.|\r\nL-S"), 4);
+    }
 }
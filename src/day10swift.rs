@@ -13,9 +13,8 @@ pub fn p2_swift(input: &str) -> u64 {
 
 #[cfg(test)]
 mod tests {
-    use std::{fs::File, io::Read};
-
     use super::*;
+    use crate::tests::read_input;
 
     #[test]
     fn test_swift_sample() {
@@ -36,18 +35,14 @@ L7JLJL-JLJLJL--JLJ.L
 
     #[test]
     fn test_swift_part1() {
-        let mut f = File::open("input.txt").expect("can't open file");
-        let mut buf = String::new();
-        f.read_to_string(&mut buf).expect("can't read file");
+        let buf = read_input();
         let result = p1_swift(&buf);
         assert_eq!(result, 6778);
     }
 
     #[test]
     fn test_swift_part2() {
-        let mut f = File::open("input.txt").expect("can't open file");
-        let mut buf = String::new();
-        f.read_to_string(&mut buf).expect("can't read file");
+        let buf = read_input();
         let result = p2_swift(&buf);
         assert_eq!(result, 433);
     }
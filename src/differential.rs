@@ -0,0 +1,223 @@
+//! Differential tests: all implementations of both parts must agree on
+//! thousands of generated maps, and with the answers the generator
+//! expects. A map the implementations disagree on is
+//! shrunk to a minimal one before it is reported.
+
+use crate::generator::{generate, GeneratorOptions, Rng};
+use crate::{
+    p1, p2, p2_checked, p2_flood, p2_with, ParseMode, PipeMap, Strategy,
+};
+
+/// Maps that are not clean rectangles, as files from editors often are,
+/// which all implementations must read the same way.
+const UNTIDY_MAPS: [&str; 4] = [
+    "F-7\n|.|\nL-S\n\n",
+    ".....\n.S-7.\n.|.|.\n.L-J.\n.....        \n",
+    "S7\r\nLJ\r\n\r\n",
+    "..F7.\n.FJ|.\nSJ.L7\n|F--J\nLJ\n",
+];
+
+/// A random map from [`generate`] and the answers to both parts. In some
+/// of them, the loop is broken next to `S`, so there is none.
+fn random_map(rng: &mut Rng) -> (Vec<Vec<u8>>, [u64; 2]) {
+    let (width, height) = (2 + rng.below(24), 2 + rng.below(24));
+    let options = GeneratorOptions {
+        width,
+        height,
+        seed: rng.next(),
+        loop_length: rng.below(width * height),
+        junk: rng.unit(),
+    };
+    let puzzle = generate(&options).expect("the map is large enough");
+    let mut map = puzzle
+        .input
+        .lines()
+        .map(|row| row.as_bytes().to_vec())
+        .collect::<Vec<_>>();
+    if rng.below(100) < 15 {
+        let pipe_map = PipeMap::new(&puzzle.input).unwrap();
+        let next = pipe_map.loop_path().expect("there is a loop")[1];
+        map[next.y][next.x] = b'.';
+        return (map, [0, 0]);
+    }
+    (map, [puzzle.p1 as u64, puzzle.p2 as u64])
+}
+
+/// The map as puzzle input.
+fn to_input(map: &[Vec<u8>]) -> String {
+    let mut input = String::new();
+    for row in map {
+        input.extend(row.iter().map(|&tile| char::from(tile)));
+        input.push('\n');
+    }
+    input
+}
+
+/// The answers of all implementations of part 1 and part 2.
+fn answers(input: &str) -> [Vec<(&'static str, u64)>; 2] {
+    let map = PipeMap::with_mode(input.as_bytes(), ParseMode::Normalize)
+        .expect("the maps are valid");
+    #[allow(unused_mut)]
+    let mut part1 = vec![
+        ("p1", p1(input) as u64),
+        ("day10cpp::p1_cpp", crate::day10cpp::p1_cpp(input)),
+        ("PipeMap::farthest_distance", map.farthest_distance() as u64),
+    ];
+    #[allow(unused_mut)]
+    let mut part2 = vec![
+        ("p2", p2(input) as u64),
+        ("day10cpp::p2_cpp", crate::day10cpp::p2_cpp(input)),
+        ("PipeMap::enclosed_count", map.enclosed_count() as u64),
+        ("p2_checked", p2_checked(input).unwrap() as u64),
+        ("p2_flood", p2_flood(input) as u64),
+        (
+            "Strategy::Scanline",
+            p2_with(input, Strategy::Scanline) as u64,
+        ),
+        (
+            "Strategy::Corners",
+            p2_with(input, Strategy::Corners) as u64,
+        ),
+    ];
+    #[cfg(target_os = "macos")]
+    {
+        part1
+            .push(("day10swift::p1_swift", crate::day10swift::p1_swift(input)));
+        part2
+            .push(("day10swift::p2_swift", crate::day10swift::p2_swift(input)));
+    }
+    [part1, part2]
+}
+
+/// A description of the answers if they are not all the same.
+fn disagreement(input: &str) -> Option<String> {
+    let mut result = Vec::new();
+    for (part, answers) in answers(input).iter().enumerate() {
+        if answers.iter().any(|&(_, answer)| answer != answers[0].1) {
+            let details = answers
+                .iter()
+                .map(|(name, answer)| format!("  {name}: {answer}"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            result.push(format!("part {}:\n{details}", part + 1));
+        }
+    }
+    (!result.is_empty()).then(|| result.join("\n"))
+}
+
+/// Make the map as small and simple as possible while it still fails:
+/// cut off rows and columns at its borders and replace tiles by ground,
+/// as long as there is anything left to try.
+fn shrink(
+    mut map: Vec<Vec<u8>>,
+    fails: impl Fn(&[Vec<u8>]) -> bool,
+) -> Vec<Vec<u8>> {
+    let has_start =
+        |map: &[Vec<u8>]| map.iter().flatten().any(|&tile| tile == b'S');
+    loop {
+        let mut candidates = Vec::new();
+        if map.len() > 1 {
+            candidates.push(map[1..].to_vec());
+            candidates.push(map[..map.len() - 1].to_vec());
+        }
+        if map[0].len() > 1 {
+            candidates.push(map.iter().map(|row| row[1..].to_vec()).collect());
+            candidates.push(
+                map.iter()
+                    .map(|row| row[..row.len() - 1].to_vec())
+                    .collect(),
+            );
+        }
+        let smaller = candidates
+            .into_iter()
+            .find(|candidate| has_start(candidate) && fails(candidate));
+        if let Some(smaller) = smaller {
+            map = smaller;
+            continue;
+        }
+        let mut simpler = None;
+        'tiles: for (y, row) in map.iter().enumerate() {
+            for (x, &tile) in row.iter().enumerate() {
+                if tile != b'S' && tile != b'.' {
+                    let mut candidate = map.clone();
+                    candidate[y][x] = b'.';
+                    if fails(&candidate) {
+                        simpler = Some(candidate);
+                        break 'tiles;
+                    }
+                }
+            }
+        }
+        match simpler {
+            Some(simpler) => map = simpler,
+            None => return map,
+        }
+    }
+}
+
+#[test]
+fn test_implementations_agree() {
+    for input in UNTIDY_MAPS {
+        if let Some(disagreement) = disagreement(input) {
+            panic!(
+                "the implementations disagree on\n{input:?}\n{disagreement}"
+            );
+        }
+    }
+    let mut rng = Rng(10);
+    for i in 0..2000 {
+        let (map, expected) = random_map(&mut rng);
+        let input = to_input(&map);
+        if disagreement(&input).is_some() {
+            let fails =
+                |map: &[Vec<u8>]| disagreement(&to_input(map)).is_some();
+            let input = to_input(&shrink(map, fails));
+            panic!(
+                "the implementations disagree on map {i}, shrunk to\n{input}{}",
+                disagreement(&input).unwrap_or_default()
+            );
+        }
+        let [part1, part2] = answers(&input);
+        assert_eq!(
+            [part1[0].1, part2[0].1],
+            expected,
+            "wrong answers for map {i}\n{input}"
+        );
+    }
+}
+
+#[test]
+fn test_random_map() {
+    let mut rng = Rng(23);
+    let (mut loops, mut broken, mut at_border) = (0, 0, 0);
+    for _ in 0..500 {
+        let (map, _) = random_map(&mut rng);
+        let input = to_input(&map);
+        let pipe_map = PipeMap::new(&input).unwrap();
+        match pipe_map.loop_path() {
+            Some(_) => loops += 1,
+            None => broken += 1,
+        }
+        let start = pipe_map.start();
+        if start.x == 0
+            || start.y == 0
+            || start.x + 1 == pipe_map.width()
+            || start.y + 1 == pipe_map.height()
+        {
+            at_border += 1;
+        }
+    }
+    assert!(loops > 300, "{loops} maps with a loop");
+    assert!(broken > 30, "{broken} maps without a loop");
+    assert!(at_border > 30, "{at_border} maps with S at the border");
+}
+
+#[test]
+fn test_shrink() {
+    let map = ["....", ".S7.", ".LJ.", "..F."]
+        .map(|row| row.as_bytes().to_vec())
+        .to_vec();
+    let has_j =
+        |map: &[Vec<u8>]| map.iter().flatten().any(|&tile| tile == b'J');
+    assert_eq!(to_input(&shrink(map, has_j)), "S.\n.J\n");
+}
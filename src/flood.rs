@@ -0,0 +1,93 @@
+use crate::Location;
+
+/// Count the tiles enclosed by the given path, which is closed like the
+/// one returned by find_loop(), without any geometry: the loop is drawn
+/// on a grid of twice the resolution, where the gap between two pipes
+/// that are not connected is a cell of its own, and the outside is flood
+/// filled from the border. Tiles that the flood does not reach and that
+/// are not on the loop are enclosed. Only the bounding box of the loop
+/// with a margin of one cell is drawn, nothing outside of it is enclosed.
+pub(crate) fn enclosed_count(path: &[Location]) -> usize {
+    let Some(&first) = path.first() else {
+        return 0;
+    };
+    let (mut left, mut top) = (first.x, first.y);
+    let (mut right, mut bottom) = (first.x, first.y);
+    for loc in path {
+        left = left.min(loc.x);
+        top = top.min(loc.y);
+        right = right.max(loc.x);
+        bottom = bottom.max(loc.y);
+    }
+    // tile (x, y) of the bounding box is cell (2x + 1, 2y + 1)
+    let width = 2 * (right - left + 1) + 1;
+    let height = 2 * (bottom - top + 1) + 1;
+    let cell = |loc: &Location| {
+        let x = 2 * (loc.x - left) + 1;
+        let y = 2 * (loc.y - top) + 1;
+        y * width + x
+    };
+    let mut blocked = vec![false; width * height];
+    for pair in path.windows(2) {
+        let (from, to) = (cell(&pair[0]), cell(&pair[1]));
+        blocked[from] = true;
+        blocked[(from + to) / 2] = true;
+        blocked[to] = true;
+    }
+    // the margin is never blocked, so the whole outside is reachable from
+    // the top left corner
+    let mut outside = vec![false; width * height];
+    outside[0] = true;
+    let mut stack = vec![0];
+    while let Some(i) = stack.pop() {
+        let (x, y) = (i % width, i / width);
+        let neighbours = [
+            (y > 0).then(|| i - width),
+            (y + 1 < height).then(|| i + width),
+            (x > 0).then(|| i - 1),
+            (x + 1 < width).then(|| i + 1),
+        ];
+        for j in neighbours.into_iter().flatten() {
+            if !blocked[j] && !outside[j] {
+                outside[j] = true;
+                stack.push(j);
+            }
+        }
+    }
+    (1..height)
+        .step_by(2)
+        .flat_map(|y| (1..width).step_by(2).map(move |x| y * width + x))
+        .filter(|&i| !blocked[i] && !outside[i])
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::parse;
+
+    #[test]
+    fn test_enclosed_count() {
+        let path = parse("S-7\n|.|\nL-J\n").find_loop().unwrap();
+        assert_eq!(enclosed_count(&path), 1);
+        let path = parse("S7\nLJ\n").find_loop().unwrap();
+        assert_eq!(enclosed_count(&path), 0);
+        assert_eq!(enclosed_count(&[]), 0);
+    }
+
+    #[test]
+    fn test_enclosed_count_squeeze() {
+        // two pipes side by side leave a gap to squeeze through
+        let input = "..........
+.S------7.
+.|F----7|.
+.||....||.
+.||....||.
+.|L-7F-J|.
+.|..||..|.
+.L--JL--J.
+..........";
+        let path = parse(input).find_loop().unwrap();
+        assert_eq!(enclosed_count(&path), 4);
+    }
+}
@@ -0,0 +1,316 @@
+use crate::Location;
+
+/// How to generate a puzzle with [`generate`].
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct GeneratorOptions {
+    /// The number of columns of the map, at least 2.
+    pub width: usize,
+    /// The number of rows of the map, at least 2.
+    pub height: usize,
+    /// The same seed and options always give the same map.
+    pub seed: u64,
+    /// The number of tiles of the loop. It is rounded down to an even
+    /// number of at least 4, and the loop may end up shorter if the map
+    /// is too small for it. Loops through about four fifths of the tiles
+    /// fit.
+    pub loop_length: usize,
+    /// The share of the tiles off the loop that get a random pipe instead
+    /// of ground, from 0 to 1.
+    pub junk: f64,
+}
+
+impl Default for GeneratorOptions {
+    fn default() -> Self {
+        GeneratorOptions {
+            width: 140,
+            height: 140,
+            seed: 0,
+            loop_length: 10000,
+            junk: 0.75,
+        }
+    }
+}
+
+/// A generated map with the answers to both parts.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Puzzle {
+    /// The map, each row ending with a newline.
+    pub input: String,
+    pub start: Location,
+    pub loop_length: usize,
+    /// The answer to part 1.
+    pub p1: usize,
+    /// The answer to part 2.
+    pub p2: usize,
+}
+
+/// Generate a map with a single loop through `S`. The loop is the outline
+/// of a random shape of cells, where the corners of the cells are the
+/// tiles of the map. The shape is grown a cell at a time, and only by
+/// cells that keep its outline a single loop that never touches itself.
+/// This gives both answers without walking the loop: it is as long as the
+/// perimeter of the shape, and by Pick's theorem it encloses
+/// area + 1 - perimeter / 2 tiles. None if the map has less than two rows
+/// or columns.
+pub fn generate(options: &GeneratorOptions) -> Option<Puzzle> {
+    let (width, height) = (options.width, options.height);
+    if width < 2 || height < 2 {
+        return None;
+    }
+    let mut rng = Rng(options.seed);
+    let target = options.loop_length.max(4) & !1;
+    let shape = Shape::grow(width - 1, height - 1, target, &mut rng);
+
+    let mut input = vec![b'.'; (width + 1) * height];
+    let mut start = Location::default();
+    let mut start_index = rng.below(shape.perimeter);
+    for y in 0..height {
+        input[y * (width + 1) + width] = b'\n';
+        for x in 0..width {
+            let tile = &mut input[y * (width + 1) + x];
+            match shape.pipe(x, y) {
+                Some(_) if start_index == 0 => {
+                    *tile = b'S';
+                    start = Location { x, y };
+                    start_index = usize::MAX;
+                }
+                Some(pipe) => {
+                    *tile = pipe;
+                    start_index = start_index.wrapping_sub(1);
+                }
+                None if rng.unit() < options.junk => {
+                    *tile = b"|-LJ7F"[rng.below(6)];
+                }
+                None => {}
+            }
+        }
+    }
+    // a junk pipe leading into S would be a second way through it
+    let Location { x, y } = start;
+    let neighbours = [
+        (y > 0).then(|| (x, y - 1, b"|7F")),
+        (y + 1 < height).then(|| (x, y + 1, b"|LJ")),
+        (x > 0).then(|| (x - 1, y, b"-LF")),
+        (x + 1 < width).then(|| (x + 1, y, b"-J7")),
+    ];
+    for (x, y, into_start) in neighbours.into_iter().flatten() {
+        let tile = &mut input[y * (width + 1) + x];
+        if shape.pipe(x, y).is_none() && into_start.contains(tile) {
+            *tile = b'.';
+        }
+    }
+
+    Some(Puzzle {
+        input: String::from_utf8(input).expect("the map is ASCII"),
+        start,
+        loop_length: shape.perimeter,
+        p1: shape.perimeter / 2,
+        // Pick's theorem
+        p2: shape.area + 1 - shape.perimeter / 2,
+    })
+}
+
+/// A set of cells without holes whose outline is a single loop.
+struct Shape {
+    width: usize,
+    height: usize,
+    cells: Vec<bool>,
+    area: usize,
+    perimeter: usize,
+}
+
+/// The eight cells around a cell, clockwise from the one north of it.
+const RING: [(isize, isize); 8] = [
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+
+impl Shape {
+    /// Grow a shape from a random cell until its perimeter is `target`
+    /// or there is no cell left to add. Adding a cell next to k cells of
+    /// the shape changes the perimeter by 4 - 2k. A shape that only grew
+    /// by cells with one neighbour would be all corridors and enclose
+    /// nothing, so cells with two neighbours are added too, at half the
+    /// rate. Cells with three are never added: filling up the notches of
+    /// the shape would shrink the loop, and in the end all of the map.
+    fn grow(
+        width: usize,
+        height: usize,
+        target: usize,
+        rng: &mut Rng,
+    ) -> Shape {
+        let mut shape = Shape {
+            width,
+            height,
+            cells: vec![false; width * height],
+            area: 0,
+            perimeter: 0,
+        };
+        let mut frontier = vec![(rng.below(width), rng.below(height))];
+        while shape.perimeter < target && !frontier.is_empty() {
+            let i = rng.below(frontier.len());
+            let (x, y) = frontier[i];
+            let neighbours = if shape.area == 0 {
+                Some(0)
+            } else if shape.contains(x as isize, y as isize) {
+                None
+            } else {
+                shape.simple_neighbours(x, y)
+            };
+            let Some(neighbours) = neighbours.filter(|&n| n < 3) else {
+                frontier.swap_remove(i);
+                continue;
+            };
+            if neighbours == 2 && rng.below(2) == 0 {
+                continue;
+            }
+            frontier.swap_remove(i);
+            shape.cells[y * width + x] = true;
+            shape.area += 1;
+            shape.perimeter = shape.perimeter + 4 - 2 * neighbours;
+            for (dx, dy) in [RING[0], RING[2], RING[4], RING[6]] {
+                let (nx, ny) = (x as isize + dx, y as isize + dy);
+                let inside = (0..width as isize).contains(&nx)
+                    && (0..height as isize).contains(&ny);
+                if inside && !shape.contains(nx, ny) {
+                    frontier.push((nx as usize, ny as usize));
+                }
+            }
+        }
+        shape
+    }
+
+    fn contains(&self, x: isize, y: isize) -> bool {
+        (0..self.width as isize).contains(&x)
+            && (0..self.height as isize).contains(&y)
+            && self.cells[y as usize * self.width + x as usize]
+    }
+
+    /// The number of cells of the shape north, south, west and east of
+    /// the given cell, if adding it keeps the outline a single loop: the
+    /// cells of the shape around it must be all in one piece, and a cell
+    /// diagonal to it must not touch it by the corner only.
+    fn simple_neighbours(&self, x: usize, y: usize) -> Option<usize> {
+        let ring = RING
+            .map(|(dx, dy)| self.contains(x as isize + dx, y as isize + dy));
+        let corner_only = (1..8)
+            .step_by(2)
+            .any(|i| ring[i] && !ring[i - 1] && !ring[(i + 1) % 8]);
+        let pieces = (0..8).filter(|&i| ring[i] && !ring[(i + 7) % 8]).count();
+        (!corner_only && pieces == 1)
+            .then(|| (0..8).step_by(2).filter(|&i| ring[i]).count())
+    }
+
+    /// The pipe at tile (x, y), the corner between cells (x - 1, y - 1)
+    /// and (x, y), if the outline runs through it.
+    fn pipe(&self, x: usize, y: usize) -> Option<u8> {
+        let (x, y) = (x as isize, y as isize);
+        let north = self.contains(x - 1, y - 1) != self.contains(x, y - 1);
+        let south = self.contains(x - 1, y) != self.contains(x, y);
+        let west = self.contains(x - 1, y - 1) != self.contains(x - 1, y);
+        let east = self.contains(x, y - 1) != self.contains(x, y);
+        match (north, south, west, east) {
+            (true, true, false, false) => Some(b'|'),
+            (false, false, true, true) => Some(b'-'),
+            (true, false, false, true) => Some(b'L'),
+            (true, false, true, false) => Some(b'J'),
+            (false, true, true, false) => Some(b'7'),
+            (false, true, false, true) => Some(b'F'),
+            _ => None,
+        }
+    }
+}
+
+/// A small, seedable pseudo random number generator (SplitMix64).
+pub(crate) struct Rng(pub(crate) u64);
+
+impl Rng {
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`.
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    /// A number in `0.0..1.0`.
+    pub(crate) fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{p1, p2, PipeMap};
+
+    #[test]
+    fn test_generate() {
+        for seed in 0..20 {
+            let options = GeneratorOptions {
+                width: 30,
+                height: 20,
+                seed,
+                loop_length: 200,
+                junk: 1.0,
+            };
+            let puzzle = generate(&options).unwrap();
+            assert_eq!(puzzle.loop_length, 200);
+            assert_eq!(p1(&puzzle.input), puzzle.p1);
+            assert_eq!(p2(&puzzle.input), puzzle.p2);
+            let map = PipeMap::new(&puzzle.input).unwrap();
+            assert_eq!((map.width(), map.height()), (30, 20));
+            assert_eq!(map.start(), puzzle.start);
+            assert_eq!(generate(&options), Some(puzzle));
+        }
+        assert!(generate(&GeneratorOptions::default())
+            .is_some_and(|puzzle| puzzle.p2 > 0));
+    }
+
+    #[test]
+    fn test_generate_small() {
+        let options = GeneratorOptions {
+            width: 2,
+            height: 2,
+            loop_length: 100,
+            junk: 0.0,
+            ..Default::default()
+        };
+        let puzzle = generate(&options).unwrap();
+        assert_eq!(puzzle.input.replace('S', "F"), "F7\nLJ\n");
+        assert_eq!((puzzle.loop_length, puzzle.p1, puzzle.p2), (4, 2, 0));
+        // rounded down to an even length
+        let options = GeneratorOptions {
+            width: 10,
+            height: 10,
+            loop_length: 13,
+            junk: 0.0,
+            ..Default::default()
+        };
+        assert_eq!(generate(&options).unwrap().loop_length, 12);
+        let options = GeneratorOptions {
+            loop_length: 1000,
+            ..options
+        };
+        let puzzle = generate(&options).unwrap();
+        assert!(puzzle.loop_length < 100);
+        assert_eq!(p1(&puzzle.input), puzzle.p1);
+        assert_eq!(p2(&puzzle.input), puzzle.p2);
+        let options = GeneratorOptions {
+            height: 1,
+            ..Default::default()
+        };
+        assert_eq!(generate(&options), None);
+    }
+}
@@ -1,355 +1,208 @@
-use memchr::memchr;
-
+pub mod analysis;
 pub mod day10cpp;
 #[cfg(target_os = "macos")]
 pub mod day10swift;
+#[cfg(test)]
+mod differential;
+mod flood;
+pub mod generator;
+mod loop_index;
+mod map;
+mod polygon;
+pub mod raster;
+pub mod render;
+mod scanline;
+pub mod svg;
+
+pub use loop_index::{Containment, LoopIndex};
+use map::{parse, shoelace_with_picks_theorem, Map};
+pub use map::{AreaOverflow, Location, ParseError, ParseMode, PipeMap};
+pub use polygon::CornerPolygon;
 
 pub fn p1(input: &str) -> usize {
-    let map = parse(input);
-    if let Some(path) = map.find_loop() {
-        path.len() / 2
-    } else {
-        0
-    }
+    farthest_distance(&parse(input))
 }
 
 pub fn p2(input: &str) -> usize {
-    let map = parse(input);
-    if let Some(path) = map.find_loop() {
-        // Calculate the number of tiles enclosed
-        // by the path.
-        shoelace_with_picks_theorem(path)
-    } else {
-        0
-    }
+    enclosed_count(&parse(input))
 }
 
-/// parse the input into a Map with a starting point and a grid
-fn parse(input: &str) -> Map {
-    let mut start_pos = Location::default();
-    let lines = input
-        .lines()
-        .map(|l| l.as_bytes())
-        .enumerate()
-        .inspect(|&(y, chars)| {
-            if let Some(x) = memchr(b'S', chars) {
-                start_pos = Location { x, y };
-            }
-        })
-        .map(|(_, l)| l)
-        .collect::<Vec<_>>();
-    Map::new(start_pos, lines)
-}
-
-#[derive(Debug, PartialEq, Copy, Clone, Default)]
-struct Location {
-    x: usize,
-    y: usize,
+/// The ways [`p2_with`] can count the tiles enclosed by the loop.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum Strategy {
+    /// The shoelace formula and Pick's theorem, like [`p2`].
+    #[default]
+    Picks,
+    /// Flood filling the outside of the loop, like [`p2_flood`].
+    Flood,
+    /// Scanning each row for the tiles between crossings of the loop.
+    Scanline,
+    /// The shoelace formula and Pick's theorem over the corners of the
+    /// loop only, see [`CornerPolygon`].
+    Corners,
 }
 
-impl Location {
-    fn north(&self) -> Option<Location> {
-        if self.y > 0 {
-            Some(Location {
-                x: self.x,
-                y: self.y.saturating_sub(1),
-            })
-        } else {
-            None
-        }
-    }
-
-    fn south(&self, maxy: usize) -> Option<Location> {
-        if self.y < maxy {
-            Some(Location {
-                x: self.x,
-                y: self.y.saturating_add(1),
-            })
-        } else {
-            None
-        }
-    }
-
-    fn east(&self, maxx: usize) -> Option<Location> {
-        if self.x < maxx {
-            Some(Location {
-                x: self.x.saturating_add(1),
-                y: self.y,
-            })
-        } else {
-            None
-        }
-    }
-
-    fn west(&self) -> Option<Location> {
-        if self.x > 0 {
-            Some(Location {
-                x: self.x.saturating_sub(1),
-                y: self.y,
-            })
-        } else {
-            None
+/// Like [`p2`], but with a choice of how the enclosed tiles are counted.
+pub fn p2_with(input: &str, strategy: Strategy) -> usize {
+    let map = parse(input);
+    let enclosed = match strategy {
+        Strategy::Picks => map
+            .find_loop()
+            .map(|path| shoelace_with_picks_theorem(&path)),
+        Strategy::Flood => {
+            map.find_loop().map(|path| flood::enclosed_count(&path))
         }
-    }
+        Strategy::Scanline => map
+            .find_loop()
+            .map(|path| scanline::enclosed_count(&map, &path)),
+        Strategy::Corners => CornerPolygon::from_parsed(&map)
+            .map(|polygon| polygon.enclosed_count()),
+    };
+    enclosed.unwrap_or(0)
 }
 
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
-enum Direction {
-    North,
-    South,
-    East,
-    West,
+/// Like [`p2`], but counts the enclosed tiles by flood filling the outside
+/// of the loop instead of using the shoelace formula and Pick's theorem,
+/// as an independent cross-check.
+pub fn p2_flood(input: &str) -> usize {
+    parse(input)
+        .find_loop()
+        .map_or(0, |path| flood::enclosed_count(&path))
 }
 
-struct Map<'a> {
-    lower_right: Location,
-    map: Vec<&'a [u8]>,
-    starting_pos: Location,
+/// Like [`p2`], but without keeping the loop in memory and with checked
+/// arithmetic, so that maps too large for the shoelace sum of [`p2`] are
+/// answered with an error instead of a panic or a wrong count.
+pub fn p2_checked(input: &str) -> Result<usize, AreaOverflow> {
+    parse(input).checked_enclosed_count()
 }
 
-impl Map<'_> {
-    fn new(starting_pos: Location, map: Vec<&[u8]>) -> Map {
-        let lower_right = Location {
-            x: if let Some(&row) = map.first() {
-                row.len()
-            } else {
-                0
-            }
-            .saturating_sub(1),
-            y: map.len().saturating_sub(1),
-        };
-        Map {
-            lower_right,
-            map,
-            starting_pos,
-        }
-    }
-
-    /// Find the next tile to go to from a Location,
-    /// coming from a direction not to return to.
-    /// Return None if the next tile to go is not connected
-    /// (we hit a wall then), or is beyond the border of the map.
-    fn next_location(
-        &self,
-        loc: &Location,
-        coming_from: Direction,
-    ) -> Option<(Location, Direction)> {
-        match (self.get(*loc), coming_from) {
-            (b'|', Direction::South)
-            | (b'L', Direction::East)
-            | (b'J', Direction::West) => {
-                loc.north().map(|north| (north, Direction::South))
-            }
-            (b'|', Direction::North)
-            | (b'7', Direction::West)
-            | (b'F', Direction::East) => loc
-                .south(self.lower_right.y)
-                .map(|south| (south, Direction::North)),
-            (b'-', Direction::West)
-            | (b'L', Direction::North)
-            | (b'F', Direction::South) => loc
-                .east(self.lower_right.x)
-                .map(|east| (east, Direction::West)),
-            (b'-', Direction::East)
-            | (b'J', Direction::North)
-            | (b'7', Direction::South) => {
-                loc.west().map(|west| (west, Direction::East))
-            }
-            // this should not happen
-            _ => unreachable!("you cannot be here!"),
-        }
-        .filter(|v| match v.1 {
-            // check if we ran against a wall
-            Direction::North => {
-                memchr(self.get(v.0), &[b'S', b'|', b'L', b'J']).is_some()
-            }
-            Direction::South => {
-                memchr(self.get(v.0), &[b'S', b'|', b'7', b'F']).is_some()
-            }
-            Direction::East => {
-                memchr(self.get(v.0), &[b'S', b'-', b'L', b'F']).is_some()
-            }
-            Direction::West => {
-                memchr(self.get(v.0), &[b'S', b'-', b'7', b'J']).is_some()
-            }
-        })
-    }
-
-    /// Given a location, return the char in the map.
-    /// Note that this function does no bound checking.
-    fn get(&self, loc: Location) -> u8 {
-        self.map[loc.y][loc.x]
-    }
-
-    /// Given the location, return a list of all positions that are connected to this location.
-    fn connected_to(&self, loc: &Location) -> Vec<(Location, Direction)> {
-        let mut result = Vec::new();
-        if let Some(north) = loc.north() {
-            match self.get(north) {
-                b'S' | b'|' | b'F' | b'7' => {
-                    result.push((north, Direction::South));
-                }
-                _ => {}
-            }
-        }
-        if let Some(south) = loc.south(self.lower_right.y) {
-            match self.get(south) {
-                b'S' | b'|' | b'L' | b'J' => {
-                    result.push((south, Direction::North));
-                }
-                _ => {}
-            }
-        }
-        if let Some(west) = loc.west() {
-            match self.get(west) {
-                b'S' | b'-' | b'F' | b'L' => {
-                    result.push((west, Direction::East));
-                }
-                _ => {}
-            }
-        }
-        if let Some(east) = loc.east(self.lower_right.x) {
-            match self.get(east) {
-                b'S' | b'-' | b'J' | b'7' => {
-                    result.push((east, Direction::West));
-                }
-                _ => {}
-            }
-        }
-        result
-    }
+/// Like [`p1`], but rejects malformed maps with a [`ParseError`]
+/// instead of answering 0 or panicking.
+pub fn try_p1(input: &str) -> Result<usize, ParseError> {
+    PipeMap::new(input).map(|map| map.farthest_distance())
+}
 
-    /// Find the loop that returns to the starting point, if there is any.
-    /// The returned sequence of locations include the starting point as
-    /// the first and last location in the list. It is a closed polygon, but with
-    /// all integer coordinates in the list, not just the edges.
-    fn find_loop(&self) -> Option<Vec<Location>> {
-        let mut path_starts = self.connected_to(&self.starting_pos).into_iter();
-        // try all tiles connected to S for a loop (not all connected tiles to S may be part of a loop)
-        while let Some((mut current, mut coming_from)) = path_starts.next() {
-            let mut path = vec![self.starting_pos, current];
-            // follow the path until we reach S again or bump into a wall or
-            // the border of the map
-            while let Some((next_loc, direction)) =
-                self.next_location(&current, coming_from)
-            {
-                // this is for part2 - the funny thing is, this is faster
-                // than counting steps
-                path.push(next_loc);
-                if next_loc == self.starting_pos {
-                    return Some(path);
-                }
-                current = next_loc;
-                coming_from = direction;
-            }
-        }
+/// Like [`p2`], but rejects malformed maps with a [`ParseError`]
+/// instead of answering 0 or panicking.
+pub fn try_p2(input: &str) -> Result<usize, ParseError> {
+    PipeMap::new(input).map(|map| map.enclosed_count())
+}
 
-        None
+fn farthest_distance(map: &Map) -> usize {
+    if let Some(path) = map.find_loop() {
+        path.len() / 2
+    } else {
+        0
     }
 }
 
-/// Computes the number of enclosed tiles of the given path.
-/// This expects that the last point in the list is
-/// the same as the first point in the list, like in the return
-/// value of map.find_loop(). This function may panic with
-/// integer overflow, depending on the range of x and y coordinates
-/// in the path. So keep your map size reasonable.
-fn shoelace_with_picks_theorem(path: Vec<Location>) -> usize {
-    let n = path.len();
-    // shoelace for area
-    // we need to switch to isize arithmetic as area may
-    // become negative
-    let area = (0..n - 1)
-        .fold(0, |acc, i| {
-            // avoid bound checking is safe here
-            let xi = unsafe { path.get_unchecked(i).x } as isize;
-            let yi = unsafe { path.get_unchecked(i).y } as isize;
-            let x_next = unsafe { path.get_unchecked(i + 1).x } as isize;
-            let y_next = unsafe { path.get_unchecked(i + 1).y } as isize;
-            acc + (yi + y_next) * (xi - x_next)
-        })
-        .abs() as usize
-        / 2;
-    // Pick's theorem
-    area - (n - 1) / 2 + 1
+fn enclosed_count(map: &Map) -> usize {
+    if let Some(path) = map.find_loop() {
+        // Calculate the number of tiles enclosed
+        // by the path.
+        shoelace_with_picks_theorem(&path)
+    } else {
+        0
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use std::{fs::File, io::Read};
+    use std::fs;
 
     use super::*;
 
     #[test]
-    fn test_connected_to() {
-        let input = ".....
-.S-7.
-.|.|.
-.L-J.
-.....        
-";
-        let map = parse(input);
-        let start = map.starting_pos;
-        let connected = map.connected_to(&start);
-        assert_eq!(connected.len(), 2);
-        let expected = vec![
-            (Location { x: 1, y: 2 }, Direction::North),
-            (Location { x: 2, y: 1 }, Direction::West),
-        ];
-        assert_eq!(connected, expected);
+    fn test_p2_sample() {
+        let input = "FF7FSF7F7F7F7F7F---7
+L|LJ||||||||||||F--J
+FL-7LJLJ||||||LJL-77
+F--JF--7||LJLJIF7FJ-
+L---JF-JLJIIIIFJLJJ7
+|F|F-JF---7IIIL7L|7|
+|FFJF7L7F-JF7IIL---7
+7-L-JL7||F7|L7F-7F7|
+L.L7LFJ|||||FJL7||LJ
+L7JLJL-JLJLJL--JLJ.L";
+        let result = p2(input);
+        assert_eq!(result, 10);
     }
 
     #[test]
-    fn test_connected_to2() {
-        let input = "-L|F7
-7S-7|
-L|7||
--L-J|
-L|-JF
-";
-        let map = parse(input);
-        let start = map.starting_pos;
+    fn test_part1() {
+        let buf = read_input();
+        let result = p1(&buf);
+        assert_eq!(result, 6778);
+    }
 
-        let connected = map.connected_to(&start);
-        assert_eq!(connected.len(), 2);
-        let expected = vec![
-            (Location { x: 1, y: 2 }, Direction::North),
-            (Location { x: 2, y: 1 }, Direction::West),
-        ];
-        assert_eq!(connected, expected);
+    #[test]
+    fn test_part2() {
+        let buf = read_input();
+        let result = p2(&buf);
+        assert_eq!(result, 433);
     }
 
     #[test]
-    fn test_find_loop() {
-        let input = ".....
-.S-7.
-.|.|.
-.L-J.
-.....        
-";
-        let map = parse(input);
-        let steps = map.find_loop().unwrap().len() / 2;
-        assert_eq!(steps, 4);
+    fn test_try_parts() {
+        let buf = read_input();
+        assert_eq!(try_p1(&buf), Ok(6778));
+        assert_eq!(try_p2(&buf), Ok(433));
     }
 
     #[test]
-    fn test_shoelace() {
-        let input = ".....
-.S-7.
-.|.|.
-.L-J.
-.....        
-";
-        let map = parse(input);
-        let steps = map.find_loop().unwrap();
-        let area = shoelace_with_picks_theorem(steps);
-        assert_eq!(area, 1);
+    fn test_try_parse_errors() {
+        assert_eq!(try_p1("").unwrap_err(), ParseError::EmptyInput);
+        assert_eq!(try_p1("\n").unwrap_err(), ParseError::EmptyInput);
+        assert_eq!(try_p1("\n\n").unwrap_err(), ParseError::EmptyInput);
+        assert_eq!(
+            try_p1("\nS7\nLJ\n").unwrap_err(),
+            ParseError::RaggedRow {
+                row: 0,
+                width: 0,
+                expected: 2
+            }
+        );
+        assert_eq!(try_p1(".F7\n.LJ\n").unwrap_err(), ParseError::NoStart);
+        assert_eq!(
+            try_p1("SF7\n.LS\n").unwrap_err(),
+            ParseError::MultipleStarts { row: 1, column: 2 }
+        );
+        assert_eq!(
+            try_p2(".S-7.\n.|.|\n").unwrap_err(),
+            ParseError::RaggedRow {
+                row: 1,
+                width: 4,
+                expected: 5
+            }
+        );
+        assert_eq!(
+            try_p2(".S-7.\n.|x|.\n").unwrap_err(),
+            ParseError::IllegalByte {
+                byte: b'x',
+                row: 1,
+                column: 2
+            }
+        );
     }
 
     #[test]
-    fn test_shoelace2() {
-        let input = "...........
+    fn test_try_without_loop() {
+        assert_eq!(try_p1("S-7\n..|\n"), Ok(0));
+        assert_eq!(try_p2("S-7\n..|\n"), Ok(0));
+    }
+
+    /// The puzzle input.
+    pub(crate) fn read_input() -> String {
+        fs::read_to_string("input.txt").expect("can't read input.txt")
+    }
+
+    /// Maps with and without a loop, for cross-checking solutions.
+    pub(crate) const SAMPLES: [&str; 7] = [
+        ".....\n.S-7.\n.|.|.\n.L-J.\n.....        \n",
+        "..F7.\n.FJ|.\nSJ.L7\n|F--J\nLJ...\n",
+        "...........
 .S-------7.
 .|F-----7|.
 .||.....||.
@@ -357,87 +210,27 @@ L|-JF
 .|L-7.F-J|.
 .|..|.|..|.
 .L--J.L--J.
-...........";
-        let map = parse(input);
-        let steps = map.find_loop().unwrap();
-        let area = shoelace_with_picks_theorem(steps);
-        assert_eq!(area, 4);
-    }
-    #[test]
-    fn test_find_loop2() {
-        let input = "..F7.
-.FJ|.
-SJ.L7
-|F--J
-LJ...
-";
-        let map = parse(input);
-        let steps = map.find_loop().unwrap().len() / 2;
-        assert_eq!(steps, 8);
-    }
-
-    #[test]
-    fn test_next_tile() {
-        let input = ".....
-.F-7.
-.|.|.
-.L-J.
-.....        
-";
-        let map = parse(input);
-        let result = map
-            .next_location(&Location { x: 1, y: 1 }, Direction::South)
-            .unwrap();
-        assert_eq!(result, (Location { x: 2, y: 1 }, Direction::West));
-        let result = map
-            .next_location(&Location { x: 1, y: 1 }, Direction::East)
-            .unwrap();
-        assert_eq!(result, (Location { x: 1, y: 2 }, Direction::North));
-        let result = map
-            .next_location(&Location { x: 2, y: 1 }, Direction::West)
-            .unwrap();
-        assert_eq!(result, (Location { x: 3, y: 1 }, Direction::West));
-        let result = map
-            .next_location(&Location { x: 2, y: 1 }, Direction::East)
-            .unwrap();
-        assert_eq!(result, (Location { x: 1, y: 1 }, Direction::East));
-        let result = map
-            .next_location(&Location { x: 3, y: 1 }, Direction::West)
-            .unwrap();
-        assert_eq!(result, (Location { x: 3, y: 2 }, Direction::North));
-        let result = map
-            .next_location(&Location { x: 3, y: 1 }, Direction::South)
-            .unwrap();
-        assert_eq!(result, (Location { x: 2, y: 1 }, Direction::East));
-        let result = map
-            .next_location(&Location { x: 1, y: 2 }, Direction::North)
-            .unwrap();
-        assert_eq!(result, (Location { x: 1, y: 3 }, Direction::North));
-        let result = map
-            .next_location(&Location { x: 1, y: 2 }, Direction::South)
-            .unwrap();
-        assert_eq!(result, (Location { x: 1, y: 1 }, Direction::South));
-        let result = map
-            .next_location(&Location { x: 1, y: 3 }, Direction::North)
-            .unwrap();
-        assert_eq!(result, (Location { x: 2, y: 3 }, Direction::West));
-        let result = map
-            .next_location(&Location { x: 1, y: 3 }, Direction::East)
-            .unwrap();
-        assert_eq!(result, (Location { x: 1, y: 2 }, Direction::South));
-        let result = map
-            .next_location(&Location { x: 3, y: 3 }, Direction::North)
-            .unwrap();
-        assert_eq!(result, (Location { x: 2, y: 3 }, Direction::East));
-        let result = map
-            .next_location(&Location { x: 3, y: 3 }, Direction::West)
-            .unwrap();
-        assert_eq!(result, (Location { x: 3, y: 2 }, Direction::South));
-    }
-
-    #[test]
-    fn test_p2_sample() {
-        let input = "FF7FSF7F7F7F7F7F---7
+...........",
+        "..........
+.S------7.
+.|F----7|.
+.||....||.
+.||....||.
+.|L-7F-J|.
+.|..||..|.
+.L--JL--J.
+..........",
+        ".F----7F7F7F7F-7....
+.|F--7||||||||FJ....
+.||.FJ||||||||L7....
+FJL7L7LJLJ||LJ.L-7..
+L--J.L7...LJS7F-7L7.
+....F-J..F7FJ|L7L7L7
+....L7.F7||L7|.L7L7|
+.....|FJLJ|FJ|F7|.LJ
+....FJL-7.||.||||...
+....L---J.LJ.LJLJ...",
+        "FF7FSF7F7F7F7F7F---7
 L|LJ||||||||||||F--J
 FL-7LJLJ||||||LJL-77
 F--JF--7||LJLJIF7FJ-
@@ -446,26 +239,52 @@ L---JF-JLJIIIIFJLJJ7
 |FFJF7L7F-JF7IIL---7
 7-L-JL7||F7|L7F-7F7|
 L.L7LFJ|||||FJL7||LJ
-L7JLJL-JLJLJL--JLJ.L";
-        let result = p2(input);
-        assert_eq!(result, 10);
+L7JLJL-JLJLJL--JLJ.L",
+        "S-7\n..|\n",
+    ];
+
+    #[test]
+    fn test_p2_flood() {
+        for input in SAMPLES {
+            assert_eq!(p2_flood(input), p2(input), "{input}");
+        }
+        let buf = read_input();
+        assert_eq!(p2_flood(&buf), p2(&buf));
+        assert_eq!(p2_flood(&buf), 433);
     }
 
     #[test]
-    fn test_part1() {
-        let mut f = File::open("input.txt").expect("can't open file");
-        let mut buf = String::new();
-        f.read_to_string(&mut buf).expect("can't read file");
-        let result = p1(&buf);
-        assert_eq!(result, 6778);
+    fn test_strategies_on_samples() {
+        for input in SAMPLES {
+            for strategy in [
+                Strategy::Picks,
+                Strategy::Flood,
+                Strategy::Scanline,
+                Strategy::Corners,
+            ] {
+                assert_eq!(p2_with(input, strategy), p2(input), "{input}");
+            }
+        }
     }
 
     #[test]
-    fn test_part2() {
-        let mut f = File::open("input.txt").expect("can't open file");
-        let mut buf = String::new();
-        f.read_to_string(&mut buf).expect("can't read file");
-        let result = p2(&buf);
-        assert_eq!(result, 433);
+    fn test_p2_with() {
+        let buf = read_input();
+        for strategy in [
+            Strategy::Picks,
+            Strategy::Flood,
+            Strategy::Scanline,
+            Strategy::Corners,
+        ] {
+            assert_eq!(p2_with(&buf, strategy), 433);
+        }
+        assert_eq!(p2_with("S-7\n..|\n", Strategy::Scanline), 0);
+    }
+
+    #[test]
+    fn test_p2_checked() {
+        let buf = read_input();
+        assert_eq!(p2_checked(&buf), Ok(433));
+        assert_eq!(p2_checked("S-7\n..|\n"), Ok(0));
     }
 }
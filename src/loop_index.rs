@@ -0,0 +1,147 @@
+use crate::Location;
+
+/// Where a tile is relative to the loop.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Containment {
+    Inside,
+    Outside,
+    OnLoop,
+}
+
+/// The loop tiles of a single row.
+#[derive(Debug, Default, Clone)]
+struct Row {
+    /// Columns of loop tiles with a pipe leading north, sorted. Passing
+    /// one of those from left to right crosses the loop.
+    crossings: Vec<usize>,
+    /// Runs of loop tiles as inclusive column ranges, sorted.
+    on_loop: Vec<(usize, usize)>,
+}
+
+/// An index over a loop answering whether a tile is inside, outside or on
+/// the loop in logarithmic time. Build it once when asking for many tiles.
+#[derive(Debug, Clone)]
+pub struct LoopIndex {
+    rows: Vec<Row>,
+}
+
+impl LoopIndex {
+    /// Build the index from a closed path that starts and ends with the
+    /// same location, like [`crate::PipeMap::loop_path`] returns.
+    pub fn new(path: &[Location]) -> LoopIndex {
+        let height = path.iter().map(|loc| loc.y + 1).max().unwrap_or(0);
+        let mut rows = vec![Row::default(); height];
+        let mut columns = vec![Vec::new(); height];
+        for loc in path.iter().skip(1) {
+            columns[loc.y].push(loc.x);
+        }
+        // a vertical step connects the lower tile to the north
+        for step in path.windows(2) {
+            if step[0].y != step[1].y {
+                let lower = if step[0].y > step[1].y {
+                    step[0]
+                } else {
+                    step[1]
+                };
+                rows[lower.y].crossings.push(lower.x);
+            }
+        }
+        for (row, mut columns) in rows.iter_mut().zip(columns) {
+            row.crossings.sort_unstable();
+            columns.sort_unstable();
+            for x in columns {
+                match row.on_loop.last_mut() {
+                    Some((_, end)) if *end + 1 == x => *end = x,
+                    _ => row.on_loop.push((x, x)),
+                }
+            }
+        }
+        LoopIndex { rows }
+    }
+
+    /// Tell whether the tile at the given location is inside, outside or
+    /// on the loop.
+    pub fn locate(&self, loc: Location) -> Containment {
+        let Some(row) = self.rows.get(loc.y) else {
+            return Containment::Outside;
+        };
+        // the last run starting at or before x
+        let run = row.on_loop.partition_point(|&(start, _)| start <= loc.x);
+        if run > 0 && row.on_loop[run - 1].1 >= loc.x {
+            return Containment::OnLoop;
+        }
+        let crossed = row.crossings.partition_point(|&x| x < loc.x);
+        if crossed % 2 == 1 {
+            Containment::Inside
+        } else {
+            Containment::Outside
+        }
+    }
+
+    /// Shorthand for `locate(loc) == Containment::Inside`.
+    pub fn is_inside(&self, loc: Location) -> bool {
+        self.locate(loc) == Containment::Inside
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::tests::read_input;
+    use crate::PipeMap;
+
+    #[test]
+    fn test_locate() {
+        let input = "...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+...........";
+        let map = PipeMap::new(input).unwrap();
+        let index = map.loop_index().unwrap();
+        assert_eq!(index.locate(Location { x: 0, y: 0 }), Containment::Outside);
+        assert_eq!(index.locate(Location { x: 1, y: 1 }), Containment::OnLoop);
+        assert_eq!(index.locate(Location { x: 5, y: 1 }), Containment::OnLoop);
+        assert_eq!(index.locate(Location { x: 5, y: 3 }), Containment::Outside);
+        assert_eq!(index.locate(Location { x: 5, y: 6 }), Containment::Outside);
+        assert_eq!(index.locate(Location { x: 2, y: 6 }), Containment::Inside);
+        assert_eq!(index.locate(Location { x: 8, y: 6 }), Containment::Inside);
+        assert_eq!(
+            index.locate(Location { x: 10, y: 6 }),
+            Containment::Outside
+        );
+        assert_eq!(
+            index.locate(Location { x: 3, y: 42 }),
+            Containment::Outside
+        );
+        assert!(index.is_inside(Location { x: 3, y: 6 }));
+    }
+
+    #[test]
+    fn test_locate_input() {
+        let input = read_input();
+        let map = PipeMap::new(&input).unwrap();
+        let index = map.loop_index().unwrap();
+        let enclosed = map.enclosed_tiles().into_iter().collect::<HashSet<_>>();
+        let on_loop = map.loop_path().unwrap().iter().collect::<HashSet<_>>();
+        for y in 0..map.height() {
+            for x in 0..map.width() {
+                let loc = Location { x, y };
+                let expected = if on_loop.contains(&loc) {
+                    Containment::OnLoop
+                } else if enclosed.contains(&loc) {
+                    Containment::Inside
+                } else {
+                    Containment::Outside
+                };
+                assert_eq!(index.locate(loc), expected, "at {loc:?}");
+            }
+        }
+    }
+}
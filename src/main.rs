@@ -1,20 +1,721 @@
-use std::{fs::File, io::Read};
+use std::{
+    env,
+    fmt::Write as _,
+    fs::{self, File},
+    io::{self, BufWriter, Read, Write},
+    process::ExitCode,
+    time::{Duration, Instant},
+};
 
-use day10::{p1, p2};
+use day10::{
+    generator::{generate, GeneratorOptions},
+    raster::Raster,
+    render::{render, RenderOptions, Style},
+    svg::{to_svg, SvgOptions},
+    ParseMode, PipeMap,
+};
 
-fn main() {
-    let mut f = File::open("input.txt").expect("can't open file");
-    let mut buf = String::new();
-    f.read_to_string(&mut buf).expect("can't read file");
-    let result = p1(&buf);
-    println!("{result}");
+const USAGE: &str = "\
+Usage: day10 [COMMAND] [OPTIONS] [FILE...]
+
+Solve AoC 2023 day 10 for each FILE, or for standard input if there is
+no FILE or FILE is -.
+
+Commands:
+  part1    print the distance to the farthest tile of the loop
+  part2    print the number of tiles enclosed by the loop
+  all      print both (default)
+  render   draw the loop and the tiles it encloses
+  svg      print an SVG image of the loop and the tiles it encloses
+  image    write a PNG or PPM image of the loop and the tiles it encloses
+  generate write a random map and print its answers to standard error
+
+Options:
+  -i, --impl IMPL   the implementation to run: rust (default), cpp or
+                    swift (macOS only); may be given more than once
+  -f, --format FMT  text (default) or json, which prints one JSON document
+                    per file with details about the map and timings
+      --normalize   accept maps with rows of different widths and trailing
+                    whitespace, filling short rows with ground and
+                    reporting them
+      --ascii       render with the pipe symbols of the puzzle instead of
+                    box-drawing characters
+      --color       render with ANSI colours
+      --scale N     the size of a tile in images in pixels (default 8)
+  -o, --output FILE write the output of render, svg, image or generate to
+                    FILE instead of standard output; images are written
+                    as PPM if FILE ends with .ppm and as PNG otherwise
+      --width N     the width of a generated map (default 140)
+      --height N    the height of a generated map (default 140)
+      --seed N      the seed of a generated map (default 0)
+      --length N    the length of the loop of a generated map (default
+                    10000)
+      --junk X      the share of the tiles off the loop of a generated map
+                    that are pipes, from 0 to 1 (default 0.75)
+  -h, --help        print this help
+";
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Command {
+    Part1,
+    Part2,
+    All,
+    Render,
+    Svg,
+    Image,
+    Generate,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Format {
+    Text,
+    Json,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Implementation {
+    Rust,
+    Cpp,
     #[cfg(target_os = "macos")]
-    {
-        let result = day10::day10swift::p1_swift(&buf);
-        println!("{result}");
-    }
-    let result = day10::day10cpp::p1_cpp(&buf);
-    println!("{result}");
-    let result = p2(&buf);
-    println!("{result}");
+    Swift,
+}
+
+impl Implementation {
+    fn name(self) -> &'static str {
+        match self {
+            Implementation::Rust => "rust",
+            Implementation::Cpp => "cpp",
+            #[cfg(target_os = "macos")]
+            Implementation::Swift => "swift",
+        }
+    }
+
+    /// The answer to part 1.
+    fn part1(self, input: &str) -> u64 {
+        match self {
+            Implementation::Rust => day10::p1(input) as u64,
+            Implementation::Cpp => day10::day10cpp::p1_cpp(input),
+            #[cfg(target_os = "macos")]
+            Implementation::Swift => day10::day10swift::p1_swift(input),
+        }
+    }
+
+    /// The answer to part 2.
+    fn part2(self, input: &str) -> u64 {
+        match self {
+            Implementation::Rust => day10::p2(input) as u64,
+            Implementation::Cpp => day10::day10cpp::p2_cpp(input),
+            #[cfg(target_os = "macos")]
+            Implementation::Swift => day10::day10swift::p2_swift(input),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    command: Command,
+    implementations: Vec<Implementation>,
+    format: Format,
+    mode: ParseMode,
+    render: RenderOptions,
+    scale: f64,
+    output: Option<String>,
+    generator: GeneratorOptions,
+    files: Vec<String>,
+}
+
+/// Parse the command line arguments (without the program name).
+/// Returns Ok(None) if help was requested.
+fn parse_args(
+    args: impl IntoIterator<Item = String>,
+) -> Result<Option<Args>, String> {
+    let mut command = None;
+    let mut implementations = Vec::new();
+    let mut format = Format::Text;
+    let mut mode = ParseMode::Strict;
+    let mut render = RenderOptions::default();
+    let mut scale = SvgOptions::default().scale;
+    let mut output = None;
+    let mut generator = GeneratorOptions::default();
+    let mut files = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-i" | "--impl" => {
+                let name = args
+                    .next()
+                    .ok_or_else(|| format!("{arg} needs an implementation"))?;
+                implementations.push(parse_implementation(&name)?);
+            }
+            "-f" | "--format" => {
+                format = match args.next().as_deref() {
+                    Some("text") => Format::Text,
+                    Some("json") => Format::Json,
+                    Some(name) => return Err(format!("unknown format {name}")),
+                    None => return Err(format!("{arg} needs a format")),
+                };
+            }
+            "--normalize" => mode = ParseMode::Normalize,
+            "--ascii" => render.style = Style::Ascii,
+            "--color" => render.color = true,
+            "--scale" => {
+                scale = args
+                    .next()
+                    .and_then(|scale| scale.parse().ok())
+                    .filter(|&scale: &f64| scale > 0.0)
+                    .ok_or_else(|| format!("{arg} needs a positive number"))?;
+            }
+            "-o" | "--output" => {
+                output = Some(
+                    args.next().ok_or_else(|| format!("{arg} needs a file"))?,
+                );
+            }
+            "--width" => generator.width = parse_number(&arg, args.next())?,
+            "--height" => generator.height = parse_number(&arg, args.next())?,
+            "--seed" => generator.seed = parse_number(&arg, args.next())?,
+            "--length" => {
+                generator.loop_length = parse_number(&arg, args.next())?
+            }
+            "--junk" => {
+                generator.junk = args
+                    .next()
+                    .and_then(|junk| junk.parse().ok())
+                    .filter(|junk| (0.0..=1.0).contains(junk))
+                    .ok_or_else(|| {
+                        format!("{arg} needs a number from 0 to 1")
+                    })?;
+            }
+            "part1" | "part2" | "all" | "render" | "svg" | "image"
+            | "generate"
+                if command.is_none() && files.is_empty() =>
+            {
+                command = Some(match arg.as_str() {
+                    "part1" => Command::Part1,
+                    "part2" => Command::Part2,
+                    "render" => Command::Render,
+                    "svg" => Command::Svg,
+                    "image" => Command::Image,
+                    "generate" => Command::Generate,
+                    _ => Command::All,
+                });
+            }
+            "-" => files.push(arg),
+            _ if arg.starts_with('-') => {
+                return Err(format!("unknown option {arg}"))
+            }
+            _ => files.push(arg),
+        }
+    }
+    if output.is_some() && files.len() > 1 {
+        return Err("--output needs a single input file".to_string());
+    }
+    let writes_output = matches!(
+        command,
+        Some(
+            Command::Render | Command::Svg | Command::Image | Command::Generate
+        )
+    );
+    if output.is_some() && !writes_output {
+        return Err("--output only works with render, svg, image and generate"
+            .to_string());
+    }
+    if command == Some(Command::Generate) {
+        if !files.is_empty() {
+            return Err("generate takes no input files".to_string());
+        }
+        if generator.width < 2 || generator.height < 2 {
+            return Err(
+                "a generated map needs at least 2 rows and columns".to_string()
+            );
+        }
+    }
+    if implementations.is_empty() {
+        implementations.push(Implementation::Rust);
+    }
+    Ok(Some(Args {
+        command: command.unwrap_or(Command::All),
+        implementations,
+        format,
+        mode,
+        render,
+        scale,
+        output,
+        generator,
+        files,
+    }))
+}
+
+/// Parse the value of an option that needs a whole number.
+fn parse_number<T: std::str::FromStr>(
+    arg: &str,
+    value: Option<String>,
+) -> Result<T, String> {
+    value
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| format!("{arg} needs a whole number"))
+}
+
+fn parse_implementation(name: &str) -> Result<Implementation, String> {
+    match name {
+        "rust" => Ok(Implementation::Rust),
+        "cpp" => Ok(Implementation::Cpp),
+        #[cfg(target_os = "macos")]
+        "swift" => Ok(Implementation::Swift),
+        #[cfg(not(target_os = "macos"))]
+        "swift" => {
+            Err("the swift implementation is only available on macOS"
+                .to_string())
+        }
+        _ => Err(format!("unknown implementation {name}")),
+    }
+}
+
+/// Read a file, or standard input for "-".
+fn read_input(file: &str) -> io::Result<String> {
+    if file == "-" {
+        let mut buf = String::new();
+        io::stdin().read_to_string(&mut buf)?;
+        Ok(buf)
+    } else {
+        fs::read_to_string(file)
+    }
+}
+
+/// The input without the blank lines that editors and shells like to leave
+/// at the end of a file.
+fn trim_blank_lines(input: &str) -> &str {
+    input.trim_end_matches(['\n', '\r'])
+}
+
+/// Solve the puzzle in one file and print the answers in the requested
+/// format. Returns false if the file could not be read or parsed.
+fn solve(args: &Args, file: &str) -> bool {
+    let source = if file == "-" { "<stdin>" } else { file };
+    let input = match read_input(file) {
+        Ok(input) => input,
+        Err(err) => {
+            report_error(args, source, &err.to_string());
+            return false;
+        }
+    };
+    let input = trim_blank_lines(&input);
+    let map = match PipeMap::with_mode(input.as_bytes(), args.mode) {
+        Ok(map) => map,
+        Err(err) => {
+            report_error(args, source, &err.to_string());
+            return false;
+        }
+    };
+    if !map.ragged_rows().is_empty() && args.format == Format::Text {
+        let rows = map.ragged_rows().iter().map(|row| row.to_string());
+        eprintln!(
+            "{source}: padded rows {} to {} tiles with ground",
+            rows.collect::<Vec<_>>().join(", "),
+            map.width()
+        );
+    }
+    let input = solver_input(args, input, &map);
+    let input = input.as_str();
+    let written = match (args.command, args.format) {
+        (Command::Render, _) => write_text(args, &render(&map, &args.render)),
+        (Command::Svg, _) => {
+            write_text(args, &to_svg(&map, &SvgOptions { scale: args.scale }))
+        }
+        (Command::Image, _) => write_image(args, &map),
+        (_, Format::Text) => {
+            print_text(args, source, input);
+            Ok(())
+        }
+        (_, Format::Json) => {
+            println!("{}", json_report(args, source, input, &map));
+            Ok(())
+        }
+    };
+    if let Err(err) = written {
+        let target = args.output.as_deref().unwrap_or("<stdout>");
+        eprintln!("{target}: {err}");
+        return false;
+    }
+    true
+}
+
+/// The input for the solvers, which do not normalize the map themselves:
+/// in [`ParseMode::Normalize`] the map as it was parsed, with its short
+/// rows padded.
+fn solver_input(args: &Args, input: &str, map: &PipeMap) -> String {
+    match args.mode {
+        ParseMode::Strict => input.to_string(),
+        ParseMode::Normalize => map.padded(),
+    }
+}
+
+/// Write text to the output file or standard output.
+fn write_text(args: &Args, text: &str) -> io::Result<()> {
+    match &args.output {
+        Some(file) => fs::write(file, text),
+        None => io::stdout().lock().write_all(text.as_bytes()),
+    }
+}
+
+/// Write a generated map to the output file or standard output and print
+/// its answers to standard error, so the map can be piped into another
+/// run. Returns false if the file could not be written.
+fn write_generated(args: &Args) -> bool {
+    let puzzle = generate(&args.generator).expect("the map is large enough");
+    if let Err(err) = write_text(args, &puzzle.input) {
+        let target = args.output.as_deref().unwrap_or("<stdout>");
+        eprintln!("{target}: {err}");
+        return false;
+    }
+    eprintln!("part 1: {}", puzzle.p1);
+    eprintln!("part 2: {}", puzzle.p2);
+    true
+}
+
+/// Paint the map and write it to the output file or standard output.
+fn write_image(args: &Args, map: &PipeMap) -> io::Result<()> {
+    let raster = Raster::new(map, args.scale.round() as usize);
+    match &args.output {
+        Some(file) if file.ends_with(".ppm") => {
+            let mut out = BufWriter::new(File::create(file)?);
+            raster.write_ppm(&mut out)?;
+            out.flush()
+        }
+        Some(file) => {
+            let mut out = BufWriter::new(File::create(file)?);
+            raster.write_png(&mut out)?;
+            out.flush()
+        }
+        None => {
+            let mut out = BufWriter::new(io::stdout().lock());
+            raster.write_png(&mut out)?;
+            out.flush()
+        }
+    }
+}
+
+fn report_error(args: &Args, source: &str, err: &str) {
+    match args.format {
+        Format::Text => eprintln!("{source}: {err}"),
+        Format::Json => println!(
+            "{{\"file\":{},\"error\":{}}}",
+            json_string(source),
+            json_string(err)
+        ),
+    }
+}
+
+/// Print one labeled line per answer.
+fn print_text(args: &Args, source: &str, input: &str) {
+    for &implementation in &args.implementations {
+        let name = implementation.name();
+        if args.command != Command::Part2 {
+            let answer = implementation.part1(input);
+            println!("{source}: part 1 ({name}): {answer}");
+        }
+        if args.command != Command::Part1 {
+            let answer = implementation.part2(input);
+            println!("{source}: part 2 ({name}): {answer}");
+        }
+    }
+}
+
+/// Describe the map and the answers of all requested implementations,
+/// with the time each of them took, as a single line of JSON.
+fn json_report(
+    args: &Args,
+    source: &str,
+    input: &str,
+    map: &PipeMap,
+) -> String {
+    let mut json = String::new();
+    let start = map.start();
+    let _ = write!(
+        json,
+        "{{\"file\":{},\"width\":{},\"height\":{},\
+         \"ragged_rows\":{},\"start\":{{\"x\":{},\"y\":{}}},\
+         \"start_tile\":{},\"loop_length\":{},\"farthest_distance\":{},\
+         \"enclosed\":{},\"timings\":[",
+        json_string(source),
+        map.width(),
+        map.height(),
+        json_list(map.ragged_rows()),
+        start.x,
+        start.y,
+        map.start_tile().map_or("null".to_string(), |tile| {
+            json_string(&char::from(tile).to_string())
+        }),
+        map.loop_path()
+            .map_or("null".to_string(), |path| (path.len() - 1).to_string()),
+        map.farthest_distance(),
+        map.enclosed_count(),
+    );
+    for (i, &implementation) in args.implementations.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        let _ = write!(
+            json,
+            "{{\"implementation\":{}",
+            json_string(implementation.name())
+        );
+        if args.command != Command::Part2 {
+            let timing = timed(|| implementation.part1(input));
+            let _ = write!(json, ",\"part1\":{}", json_timing(timing));
+        }
+        if args.command != Command::Part1 {
+            let timing = timed(|| implementation.part2(input));
+            let _ = write!(json, ",\"part2\":{}", json_timing(timing));
+        }
+        json.push('}');
+    }
+    json.push_str("]}");
+    json
+}
+
+/// Run a solver and measure how long it took.
+fn timed(solver: impl FnOnce() -> u64) -> (u64, Duration) {
+    let now = Instant::now();
+    let answer = solver();
+    (answer, now.elapsed())
+}
+
+fn json_timing((answer, duration): (u64, Duration)) -> String {
+    format!("{{\"answer\":{answer},\"nanos\":{}}}", duration.as_nanos())
+}
+
+/// Write a list of numbers as a JSON array.
+fn json_list(numbers: &[usize]) -> String {
+    let numbers = numbers.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+    format!("[{}]", numbers.join(","))
+}
+
+/// Quote a string for JSON.
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("day10: {err}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    if args.command == Command::Generate {
+        return if write_generated(&args) {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        };
+    }
+    let files = if args.files.is_empty() {
+        vec!["-".to_string()]
+    } else {
+        args.files.clone()
+    };
+    let mut success = true;
+    for file in &files {
+        success &= solve(&args, file);
+    }
+    if success {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Result<Option<Args>, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            args(&[]),
+            Ok(Some(Args {
+                command: Command::All,
+                implementations: vec![Implementation::Rust],
+                format: Format::Text,
+                mode: ParseMode::Strict,
+                render: RenderOptions::default(),
+                scale: 8.0,
+                output: None,
+                generator: GeneratorOptions::default(),
+                files: vec![],
+            }))
+        );
+        assert_eq!(
+            args(&[
+                "part2", "-i", "cpp", "--impl", "rust", "-f", "json", "a.txt",
+                "-"
+            ]),
+            Ok(Some(Args {
+                command: Command::Part2,
+                implementations: vec![
+                    Implementation::Cpp,
+                    Implementation::Rust
+                ],
+                format: Format::Json,
+                mode: ParseMode::Strict,
+                render: RenderOptions::default(),
+                scale: 8.0,
+                output: None,
+                generator: GeneratorOptions::default(),
+                files: vec!["a.txt".to_string(), "-".to_string()],
+            }))
+        );
+        // a file may be named like a command
+        assert_eq!(
+            args(&["part1", "all"]).unwrap().unwrap().files,
+            vec!["all".to_string()]
+        );
+        assert_eq!(
+            args(&["render", "--ascii", "--color"])
+                .unwrap()
+                .unwrap()
+                .render,
+            RenderOptions {
+                style: Style::Ascii,
+                color: true
+            }
+        );
+        assert_eq!(
+            args(&["svg", "--scale", "2.5"]).unwrap().unwrap().scale,
+            2.5
+        );
+        assert_eq!(
+            args(&["image", "-o", "loop.png", "a.txt"])
+                .unwrap()
+                .unwrap()
+                .output,
+            Some("loop.png".to_string())
+        );
+        assert!(args(&["image", "-o", "loop.png", "a.txt", "b.txt"]).is_err());
+        assert_eq!(
+            args(&["svg", "--output", "loop.svg"])
+                .unwrap()
+                .unwrap()
+                .output,
+            Some("loop.svg".to_string())
+        );
+        assert!(args(&["render", "-o", "loop.txt"]).is_ok());
+        assert!(args(&["part1", "-o", "x"]).is_err());
+        assert!(args(&["-o", "x", "a.txt"]).is_err());
+        assert!(args(&["svg", "--scale", "0"]).is_err());
+        assert_eq!(args(&["all", "--help"]), Ok(None));
+        assert!(args(&["--impl"]).is_err());
+        assert!(args(&["--impl", "java"]).is_err());
+        assert!(args(&["--verbose"]).is_err());
+        assert!(args(&["--format", "xml"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_args_generate() {
+        let parsed = args(&[
+            "generate", "--width", "64", "--height", "32", "--seed", "7",
+            "--length", "500", "--junk", "0.5",
+        ])
+        .unwrap()
+        .unwrap();
+        assert_eq!(parsed.command, Command::Generate);
+        assert_eq!(
+            parsed.generator,
+            GeneratorOptions {
+                width: 64,
+                height: 32,
+                seed: 7,
+                loop_length: 500,
+                junk: 0.5,
+            }
+        );
+        assert!(args(&["generate", "a.txt"]).is_err());
+        assert!(args(&["generate", "--width", "1"]).is_err());
+        assert!(args(&["generate", "--seed", "-1"]).is_err());
+        assert!(args(&["generate", "--junk", "2"]).is_err());
+    }
+
+    #[test]
+    fn test_trailing_blank_lines() {
+        let input = trim_blank_lines("S7\nLJ\n\n");
+        assert_eq!(input, "S7\nLJ");
+        let mode = args(&[]).unwrap().unwrap().mode;
+        let map = PipeMap::with_mode(input.as_bytes(), mode).unwrap();
+        assert_eq!(map.farthest_distance(), 2);
+        assert_eq!(trim_blank_lines("S7\r\nLJ\r\n\r\n"), "S7\r\nLJ");
+        let mode = args(&["--normalize"]).unwrap().unwrap().mode;
+        assert_eq!(mode, ParseMode::Normalize);
+        let input = trim_blank_lines("S7 \nLJ\n\n");
+        let map = PipeMap::with_mode(input.as_bytes(), mode).unwrap();
+        assert_eq!(map.farthest_distance(), 2);
+    }
+
+    #[test]
+    fn test_solver_input() {
+        let args = args(&["--normalize", "-f", "json", "-i", "cpp"])
+            .unwrap()
+            .unwrap();
+        let input = ".\nS-7\nL-J\n";
+        let map = PipeMap::with_mode(input.as_bytes(), args.mode).unwrap();
+        let input = solver_input(&args, input, &map);
+        assert_eq!(input, "...\nS-7\nL-J\n");
+        assert_eq!(Implementation::Rust.part1(&input), 3);
+        assert_eq!(Implementation::Cpp.part1(&input), 3);
+        let json = json_report(&args, "ragged", &input, &map);
+        assert!(json.contains("\"ragged_rows\":[0],"));
+        assert!(json.contains("\"part1\":{\"answer\":3,"));
+    }
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("input.txt"), "\"input.txt\"");
+        assert_eq!(
+            json_string("a \"b\"\\c\nd\u{1}"),
+            "\"a \\\"b\\\"\\\\c\\nd\\u0001\""
+        );
+    }
+
+    #[test]
+    fn test_json_report() {
+        let args = args(&["part1", "--format", "json"]).unwrap().unwrap();
+        let input = ".....\n.S-7.\n.|.|.\n.L-J.\n.....\n";
+        let map = PipeMap::new(input).unwrap();
+        let json = json_report(&args, "sample", input, &map);
+        let (head, timings) = json.split_once("\"nanos\"").unwrap();
+        assert_eq!(
+            head,
+            "{\"file\":\"sample\",\"width\":5,\"height\":5,\
+             \"ragged_rows\":[],\"start\":{\"x\":1,\"y\":1},\
+             \"start_tile\":\"F\",\"loop_length\":8,\"farthest_distance\":4,\
+             \"enclosed\":1,\"timings\":[{\"implementation\":\"rust\",\
+             \"part1\":{\"answer\":4,"
+        );
+        assert!(timings.ends_with("}}]}"));
+    }
 }
@@ -0,0 +1,1286 @@
+#![forbid(unsafe_code)]
+
+use std::fmt;
+
+use memchr::memchr;
+
+use crate::{Containment, LoopIndex};
+
+/// The reasons why an input is not a valid map.
+/// Rows and columns are zero-based.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum ParseError {
+    /// The input has no tiles at all.
+    EmptyInput,
+    /// There is no starting tile `S` in the map.
+    NoStart,
+    /// There is a second starting tile `S` at the given position.
+    MultipleStarts { row: usize, column: usize },
+    /// The row has `width` tiles, while the first row has `expected` tiles.
+    RaggedRow {
+        row: usize,
+        width: usize,
+        expected: usize,
+    },
+    /// The byte at the given position is neither a pipe, ground nor `S`.
+    IllegalByte { byte: u8, row: usize, column: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::EmptyInput => write!(f, "the map is empty"),
+            ParseError::NoStart => write!(f, "the map has no starting tile S"),
+            ParseError::MultipleStarts { row, column } => write!(
+                f,
+                "another starting tile S in row {row}, column {column}"
+            ),
+            ParseError::RaggedRow {
+                row,
+                width,
+                expected,
+            } => write!(
+                f,
+                "row {row} has {width} tiles, but {expected} were expected"
+            ),
+            ParseError::IllegalByte { byte, row, column } => write!(
+                f,
+                "illegal tile {:?} in row {row}, column {column}",
+                char::from(*byte)
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The area enclosed by a loop is too large to be computed, see
+/// [`crate::p2_checked`].
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct AreaOverflow;
+
+impl fmt::Display for AreaOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the area enclosed by the loop overflows")
+    }
+}
+
+impl std::error::Error for AreaOverflow {}
+
+/// Split the input into lines like `str::lines` does, but on bytes.
+fn lines(input: &[u8]) -> impl Iterator<Item = &[u8]> {
+    input.split_inclusive(|&b| b == b'\n').map(|line| {
+        match line.strip_suffix(b"\n") {
+            Some(line) => line.strip_suffix(b"\r").unwrap_or(line),
+            None => line,
+        }
+    })
+}
+
+/// How [`PipeMap::with_mode`] deals with input that is not a clean grid.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum ParseMode {
+    /// Reject anything but rows of tiles of the same width, separated by
+    /// `\n` or `\r\n`. The last row may end with a line break or not, but
+    /// a blank line after it is a row without tiles and so rejected.
+    #[default]
+    Strict,
+    /// Accept input as it comes out of an editor: trailing whitespace
+    /// including stray `\r` is stripped from each row, blank lines at the
+    /// end are dropped and rows shorter than the longest one are padded
+    /// with ground. Rows of a different width are reported, not rejected.
+    Normalize,
+}
+
+/// Parse the input into a Map like [`parse`] does, but check that the map
+/// is rectangular, consists of known tiles only and has exactly one
+/// starting point. In [`ParseMode::Normalize`] the map is as wide as its
+/// longest row, and the rows with fewer tiles are returned along with it.
+pub(crate) fn try_parse(
+    input: &[u8],
+    mode: ParseMode,
+) -> Result<(Map<'_>, Vec<usize>), ParseError> {
+    let mut lines = lines(input).collect::<Vec<_>>();
+    let expected = match mode {
+        // rows before the first one with tiles are reported as ragged
+        ParseMode::Strict => lines
+            .iter()
+            .map(|row| row.len())
+            .find(|&width| width > 0)
+            .unwrap_or(0),
+        ParseMode::Normalize => {
+            for row in lines.iter_mut() {
+                while let [rest @ .., last] = row {
+                    if !last.is_ascii_whitespace() {
+                        break;
+                    }
+                    *row = rest;
+                }
+            }
+            while lines.last().is_some_and(|row| row.is_empty()) {
+                lines.pop();
+            }
+            lines.iter().map(|row| row.len()).max().unwrap_or(0)
+        }
+    };
+    if expected == 0 {
+        return Err(ParseError::EmptyInput);
+    }
+    let mut start_pos = None;
+    let mut ragged_rows = Vec::new();
+    for (y, row) in lines.iter().enumerate() {
+        if row.len() != expected {
+            if mode == ParseMode::Strict {
+                return Err(ParseError::RaggedRow {
+                    row: y,
+                    width: row.len(),
+                    expected,
+                });
+            }
+            ragged_rows.push(y);
+        }
+        for (x, &byte) in row.iter().enumerate() {
+            match byte {
+                b'|' | b'-' | b'L' | b'J' | b'7' | b'F' | b'.' => {}
+                b'S' if start_pos.is_none() => {
+                    start_pos = Some(Location { x, y });
+                }
+                b'S' => {
+                    return Err(ParseError::MultipleStarts {
+                        row: y,
+                        column: x,
+                    })
+                }
+                _ => {
+                    return Err(ParseError::IllegalByte {
+                        byte,
+                        row: y,
+                        column: x,
+                    })
+                }
+            }
+        }
+    }
+    let start_pos = start_pos.ok_or(ParseError::NoStart)?;
+    Ok((Map::with_width(start_pos, lines, expected), ragged_rows))
+}
+
+/// parse the input into a Map with a starting point and a grid
+pub(crate) fn parse(input: &str) -> Map<'_> {
+    let mut start_pos = Location::default();
+    let lines = input
+        .lines()
+        .map(|l| l.as_bytes())
+        .enumerate()
+        .inspect(|&(y, chars)| {
+            if let Some(x) = memchr(b'S', chars) {
+                start_pos = Location { x, y };
+            }
+        })
+        .map(|(_, l)| l)
+        .collect::<Vec<_>>();
+    Map::new(start_pos, lines)
+}
+
+/// The position of a tile, `x` counting columns from the left and `y`
+/// counting rows from the top, both starting at 0.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, Default)]
+pub struct Location {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Location {
+    fn north(&self) -> Option<Location> {
+        if self.y > 0 {
+            Some(Location {
+                x: self.x,
+                y: self.y.saturating_sub(1),
+            })
+        } else {
+            None
+        }
+    }
+
+    fn south(&self, maxy: usize) -> Option<Location> {
+        if self.y < maxy {
+            Some(Location {
+                x: self.x,
+                y: self.y.saturating_add(1),
+            })
+        } else {
+            None
+        }
+    }
+
+    fn east(&self, maxx: usize) -> Option<Location> {
+        if self.x < maxx {
+            Some(Location {
+                x: self.x.saturating_add(1),
+                y: self.y,
+            })
+        } else {
+            None
+        }
+    }
+
+    fn west(&self) -> Option<Location> {
+        if self.x > 0 {
+            Some(Location {
+                x: self.x.saturating_sub(1),
+                y: self.y,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub(crate) enum Direction {
+    North,
+    South,
+    East,
+    West,
+}
+
+pub(crate) struct Map<'a> {
+    lower_right: Location,
+    map: Vec<&'a [u8]>,
+    starting_pos: Location,
+}
+
+impl Map<'_> {
+    fn new(starting_pos: Location, map: Vec<&[u8]>) -> Map<'_> {
+        let width = map.first().map_or(0, |row| row.len());
+        Map::with_width(starting_pos, map, width)
+    }
+
+    /// A map of the given width. Rows with fewer tiles are taken to
+    /// continue with ground.
+    fn with_width(
+        starting_pos: Location,
+        map: Vec<&[u8]>,
+        width: usize,
+    ) -> Map<'_> {
+        let lower_right = Location {
+            x: width.saturating_sub(1),
+            y: map.len().saturating_sub(1),
+        };
+        Map {
+            lower_right,
+            map,
+            starting_pos,
+        }
+    }
+
+    /// Find the next tile to go to from a Location,
+    /// coming from a direction not to return to.
+    /// Return None if the next tile to go is not connected
+    /// (we hit a wall then), or is beyond the border of the map.
+    fn next_location(
+        &self,
+        loc: &Location,
+        coming_from: Direction,
+    ) -> Option<(Location, Direction)> {
+        match (self.get(*loc), coming_from) {
+            (b'|', Direction::South)
+            | (b'L', Direction::East)
+            | (b'J', Direction::West) => {
+                loc.north().map(|north| (north, Direction::South))
+            }
+            (b'|', Direction::North)
+            | (b'7', Direction::West)
+            | (b'F', Direction::East) => loc
+                .south(self.lower_right.y)
+                .map(|south| (south, Direction::North)),
+            (b'-', Direction::West)
+            | (b'L', Direction::North)
+            | (b'F', Direction::South) => loc
+                .east(self.lower_right.x)
+                .map(|east| (east, Direction::West)),
+            (b'-', Direction::East)
+            | (b'J', Direction::North)
+            | (b'7', Direction::South) => {
+                loc.west().map(|west| (west, Direction::East))
+            }
+            // S, ground or a pipe we cannot have entered from that side
+            _ => None,
+        }
+        .filter(|v| match v.1 {
+            // check if we ran against a wall
+            Direction::North => memchr(self.get(v.0), b"S|LJ").is_some(),
+            Direction::South => memchr(self.get(v.0), b"S|7F").is_some(),
+            Direction::East => memchr(self.get(v.0), b"S-LF").is_some(),
+            Direction::West => memchr(self.get(v.0), b"S-7J").is_some(),
+        })
+    }
+
+    /// The number of tiles in each row.
+    pub(crate) fn width(&self) -> usize {
+        self.lower_right.x + 1
+    }
+
+    /// The number of rows.
+    pub(crate) fn height(&self) -> usize {
+        self.lower_right.y + 1
+    }
+
+    /// Given a location, return the char in the map, which is ground
+    /// beyond the end of a short row.
+    /// Note that this function does no bound checking on the rows.
+    pub(crate) fn get(&self, loc: Location) -> u8 {
+        self.map[loc.y].get(loc.x).copied().unwrap_or(b'.')
+    }
+
+    /// Given the location, return a list of all positions that are connected to this location.
+    fn connected_to(&self, loc: &Location) -> Vec<(Location, Direction)> {
+        let mut result = Vec::new();
+        if let Some(north) = loc.north() {
+            match self.get(north) {
+                b'S' | b'|' | b'F' | b'7' => {
+                    result.push((north, Direction::South));
+                }
+                _ => {}
+            }
+        }
+        if let Some(south) = loc.south(self.lower_right.y) {
+            match self.get(south) {
+                b'S' | b'|' | b'L' | b'J' => {
+                    result.push((south, Direction::North));
+                }
+                _ => {}
+            }
+        }
+        if let Some(west) = loc.west() {
+            match self.get(west) {
+                b'S' | b'-' | b'F' | b'L' => {
+                    result.push((west, Direction::East));
+                }
+                _ => {}
+            }
+        }
+        if let Some(east) = loc.east(self.lower_right.x) {
+            match self.get(east) {
+                b'S' | b'-' | b'J' | b'7' => {
+                    result.push((east, Direction::West));
+                }
+                _ => {}
+            }
+        }
+        result
+    }
+
+    /// The tiles connected to the given one by pipes leading into each
+    /// other, following the rules of connected_to() and next_location().
+    /// The starting point is connected to all pipes leading into it.
+    fn pipe_neighbours(&self, loc: Location) -> Vec<Location> {
+        let openings = match self.get(loc) {
+            b'S' => {
+                return self
+                    .connected_to(&loc)
+                    .into_iter()
+                    .map(|(neighbour, _)| neighbour)
+                    .collect()
+            }
+            b'|' => [Direction::North, Direction::South],
+            b'-' => [Direction::East, Direction::West],
+            b'L' => [Direction::North, Direction::East],
+            b'J' => [Direction::North, Direction::West],
+            b'7' => [Direction::South, Direction::West],
+            b'F' => [Direction::South, Direction::East],
+            _ => return Vec::new(),
+        };
+        // coming in through one opening leads out through the other
+        openings
+            .into_iter()
+            .filter_map(|opening| self.next_location(&loc, opening))
+            .map(|(neighbour, _)| neighbour)
+            .collect()
+    }
+
+    /// Find the loop that returns to the starting point, if there is any.
+    /// The returned sequence of locations include the starting point as
+    /// the first and last location in the list. It is a closed polygon, but with
+    /// all integer coordinates in the list, not just the edges.
+    pub(crate) fn find_loop(&self) -> Option<Vec<Location>> {
+        let path_starts = self.connected_to(&self.starting_pos);
+        // try all tiles connected to S for a loop (not all connected tiles to S may be part of a loop)
+        for (mut current, mut coming_from) in path_starts {
+            let mut path = vec![self.starting_pos, current];
+            // follow the path until we reach S again or bump into a wall or
+            // the border of the map
+            while let Some((next_loc, direction)) =
+                self.next_location(&current, coming_from)
+            {
+                // this is for part2 - the funny thing is, this is faster
+                // than counting steps
+                path.push(next_loc);
+                if next_loc == self.starting_pos {
+                    return Some(path);
+                }
+                current = next_loc;
+                coming_from = direction;
+            }
+        }
+
+        None
+    }
+
+    /// Find the loop through the starting point like find_loop() does,
+    /// but only keep its corners, the tiles where it changes direction.
+    /// The corners are in the order of find_loop(), without repeating the
+    /// first one at the end, and include the starting point if it hides a
+    /// corner.
+    pub(crate) fn find_loop_corners(&self) -> Option<Vec<Location>> {
+        let start = self.starting_pos;
+        for (mut current, mut coming_from) in self.connected_to(&start) {
+            let first_step = coming_from;
+            let mut corners = Vec::new();
+            while let Some((next_loc, direction)) =
+                self.next_location(&current, coming_from)
+            {
+                if memchr(self.get(current), b"LJ7F").is_some() {
+                    corners.push(current);
+                }
+                if next_loc == start {
+                    // S is straight if the loop comes back into S from
+                    // the side opposite to the one it left through, which
+                    // is the side it entered the first tile from
+                    if direction != first_step {
+                        corners.insert(0, start);
+                    }
+                    return Some(corners);
+                }
+                current = next_loc;
+                coming_from = direction;
+            }
+        }
+        None
+    }
+
+    /// Find all closed loops of pipes in the map, not only the one through
+    /// the starting point, which comes first if there is one. The others
+    /// follow in the order of their top left tile, where their paths start
+    /// and end like the one returned by find_loop(). Every tile is walked
+    /// at most once: a pipe belongs to at most one loop, and no tile on a
+    /// path that runs into a dead end is on a loop either.
+    pub(crate) fn find_all_loops(&self) -> Vec<Vec<Location>> {
+        let width = self.width();
+        let mut visited = vec![false; width * self.height()];
+        let mut loops = Vec::new();
+        // S can be any pipe, so its loop needs find_loop()
+        let start = self.starting_pos;
+        visited[start.y * width + start.x] = true;
+        if let Some(path) = self.find_loop() {
+            for loc in &path {
+                visited[loc.y * width + loc.x] = true;
+            }
+            loops.push(path);
+        }
+        let mut path = Vec::new();
+        for y in 0..=self.lower_right.y {
+            for x in 0..width {
+                let first = Location { x, y };
+                if visited[y * width + x] {
+                    continue;
+                }
+                visited[y * width + x] = true;
+                // pretend to have entered the pipe through one of its ends
+                let mut coming_from = match self.get(first) {
+                    b'|' | b'7' | b'F' => Direction::South,
+                    b'L' | b'J' => Direction::North,
+                    b'-' => Direction::West,
+                    _ => continue,
+                };
+                path.clear();
+                path.push(first);
+                let mut current = first;
+                while let Some((next_loc, direction)) =
+                    self.next_location(&current, coming_from)
+                {
+                    if next_loc == first {
+                        path.push(first);
+                        loops.push(std::mem::take(&mut path));
+                        break;
+                    }
+                    let i = next_loc.y * width + next_loc.x;
+                    if visited[i] {
+                        // S or a tile known to lead into a dead end
+                        break;
+                    }
+                    visited[i] = true;
+                    path.push(next_loc);
+                    current = next_loc;
+                    coming_from = direction;
+                }
+            }
+        }
+        loops
+    }
+
+    /// Count the tiles enclosed by the loop through the starting point
+    /// like [`shoelace_with_picks_theorem`] does, but without keeping the
+    /// path: the shoelace sum is accumulated while walking the loop, in
+    /// i128 with checked arithmetic. 0 if there is no loop.
+    pub(crate) fn checked_enclosed_count(&self) -> Result<usize, AreaOverflow> {
+        let start = self.starting_pos;
+        for (mut current, mut coming_from) in self.connected_to(&start) {
+            let mut steps: u128 = 1;
+            let mut twice_area = shoelace_term(start, current)?;
+            while let Some((next_loc, direction)) =
+                self.next_location(&current, coming_from)
+            {
+                steps += 1;
+                twice_area = twice_area
+                    .checked_add(shoelace_term(current, next_loc)?)
+                    .ok_or(AreaOverflow)?;
+                if next_loc == start {
+                    // Pick's theorem
+                    let enclosed =
+                        twice_area.unsigned_abs() / 2 + 1 - steps / 2;
+                    return usize::try_from(enclosed).map_err(|_| AreaOverflow);
+                }
+                current = next_loc;
+                coming_from = direction;
+            }
+        }
+        Ok(0)
+    }
+
+    /// Deduce the pipe hidden under `S` from the loop through it, as
+    /// returned by find_loop(). The neighbours of `S` on the loop are the
+    /// second and the second to last location of the path.
+    pub(crate) fn start_tile(&self, path: &[Location]) -> u8 {
+        let start = self.starting_pos;
+        let side = |loc: Location| {
+            if loc.y < start.y {
+                Direction::North
+            } else if loc.y > start.y {
+                Direction::South
+            } else if loc.x > start.x {
+                Direction::East
+            } else {
+                Direction::West
+            }
+        };
+        match (side(path[1]), side(path[path.len() - 2])) {
+            (Direction::North, Direction::South)
+            | (Direction::South, Direction::North) => b'|',
+            (Direction::East, Direction::West)
+            | (Direction::West, Direction::East) => b'-',
+            (Direction::North, Direction::East)
+            | (Direction::East, Direction::North) => b'L',
+            (Direction::North, Direction::West)
+            | (Direction::West, Direction::North) => b'J',
+            (Direction::South, Direction::West)
+            | (Direction::West, Direction::South) => b'7',
+            (Direction::South, Direction::East)
+            | (Direction::East, Direction::South) => b'F',
+            // a loop never leaves and enters S on the same side
+            _ => b'S',
+        }
+    }
+}
+
+/// A validated map together with the loop through its starting tile,
+/// for callers that want to ask more than one question about a map
+/// without parsing it again. The map borrows the input it was parsed from.
+pub struct PipeMap<'a> {
+    map: Map<'a>,
+    path: Option<Vec<Location>>,
+    ragged_rows: Vec<usize>,
+}
+
+impl<'a> PipeMap<'a> {
+    /// Parse and validate the input and look for the loop through `S`.
+    pub fn new(input: &'a str) -> Result<PipeMap<'a>, ParseError> {
+        Self::from_bytes(input.as_bytes())
+    }
+
+    /// Like [`PipeMap::new`], for input that is not known to be UTF-8.
+    pub fn from_bytes(input: &'a [u8]) -> Result<PipeMap<'a>, ParseError> {
+        Self::with_mode(input, ParseMode::Strict)
+    }
+
+    /// Like [`PipeMap::from_bytes`], but with a choice of how strictly the
+    /// layout of the input is checked.
+    pub fn with_mode(
+        input: &'a [u8],
+        mode: ParseMode,
+    ) -> Result<PipeMap<'a>, ParseError> {
+        let (map, ragged_rows) = try_parse(input, mode)?;
+        let path = map.find_loop();
+        Ok(PipeMap {
+            map,
+            path,
+            ragged_rows,
+        })
+    }
+
+    /// The number of tiles in each row.
+    pub fn width(&self) -> usize {
+        self.map.width()
+    }
+
+    /// The number of rows.
+    pub fn height(&self) -> usize {
+        self.map.height()
+    }
+
+    /// The rows that were padded to the width of the map, which is only
+    /// ever the case in [`ParseMode::Normalize`].
+    pub fn ragged_rows(&self) -> &[usize] {
+        &self.ragged_rows
+    }
+
+    /// The location of the starting tile `S`.
+    pub fn start(&self) -> Location {
+        self.map.starting_pos
+    }
+
+    /// The tile at the given location, or None if it is outside the map.
+    pub fn tile(&self, loc: Location) -> Option<u8> {
+        (loc.x < self.width() && loc.y < self.height())
+            .then(|| self.map.get(loc))
+    }
+
+    /// The loop through `S`, if there is one. Like a closed polygon, it
+    /// starts and ends with the location of `S`.
+    pub fn loop_path(&self) -> Option<&[Location]> {
+        self.path.as_deref()
+    }
+
+    /// The number of steps along the loop to the tile farthest from `S`,
+    /// which is the answer to part 1. 0 if there is no loop.
+    pub fn farthest_distance(&self) -> usize {
+        self.path.as_ref().map_or(0, |path| path.len() / 2)
+    }
+
+    /// The distance of each loop tile from `S`, in the order of
+    /// [`PipeMap::loop_path`] without its closing `S`. It is the shorter
+    /// of the two ways around the loop. Empty if there is no loop.
+    pub fn loop_distances(&self) -> Vec<usize> {
+        let Some(path) = &self.path else {
+            return Vec::new();
+        };
+        let n = path.len() - 1;
+        (0..n).map(|i| i.min(n - i)).collect()
+    }
+
+    /// The loop tiles farthest from `S`. On a loop of odd length there
+    /// are two of them with the same distance. Empty if there is no loop.
+    pub fn farthest(&self) -> Vec<Location> {
+        let Some(path) = &self.path else {
+            return Vec::new();
+        };
+        let n = path.len() - 1;
+        if n % 2 == 0 {
+            vec![path[n / 2]]
+        } else {
+            vec![path[n / 2], path[n / 2 + 1]]
+        }
+    }
+
+    /// The distance from `S` for all tiles of the map, row by row.
+    /// None for tiles not on the loop.
+    pub fn distance_map(&self) -> Vec<Option<usize>> {
+        let width = self.width();
+        let mut result = vec![None; width * self.height()];
+        if let Some(path) = &self.path {
+            for (loc, distance) in path.iter().zip(self.loop_distances()) {
+                result[loc.y * width + loc.x] = Some(distance);
+            }
+        }
+        result
+    }
+
+    /// The number of tiles enclosed by the loop, which is the answer to
+    /// part 2. 0 if there is no loop.
+    pub fn enclosed_count(&self) -> usize {
+        self.path.as_deref().map_or(0, shoelace_with_picks_theorem)
+    }
+
+    /// The tiles enclosed by the loop, row by row from the top left.
+    /// Empty if there is no loop.
+    pub fn enclosed_tiles(&self) -> Vec<Location> {
+        let width = self.width();
+        self.containment_map()
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, containment)| containment == Containment::Inside)
+            .map(|(i, _)| Location {
+                x: i % width,
+                y: i / width,
+            })
+            .collect()
+    }
+
+    /// Where each tile of the map is relative to the loop, row by row.
+    /// All tiles are outside if there is no loop.
+    pub fn containment_map(&self) -> Vec<Containment> {
+        let width = self.width();
+        let mut result = vec![Containment::Outside; width * self.height()];
+        let (Some(path), Some(start_tile)) = (&self.path, self.start_tile())
+        else {
+            return result;
+        };
+        let on_loop = self.loop_mask(path);
+        // scan each row from the left, see crosses_loop()
+        for y in 0..self.height() {
+            let mut inside = false;
+            for x in 0..width {
+                let i = y * width + x;
+                let tile = self.map.get(Location { x, y });
+                if on_loop[i] {
+                    result[i] = Containment::OnLoop;
+                    if crosses_loop(tile, start_tile) {
+                        inside = !inside;
+                    }
+                } else if inside {
+                    result[i] = Containment::Inside;
+                }
+            }
+        }
+        result
+    }
+
+    /// Flags for all tiles of the map, row by row, telling whether the
+    /// tile is part of the given path.
+    fn loop_mask(&self, path: &[Location]) -> Vec<bool> {
+        let width = self.width();
+        let mut on_loop = vec![false; width * self.height()];
+        for loc in path {
+            on_loop[loc.y * width + loc.x] = true;
+        }
+        on_loop
+    }
+
+    /// The tiles connected to the given one by pipes, see
+    /// [`crate::analysis::components`].
+    pub(crate) fn pipe_neighbours(&self, loc: Location) -> Vec<Location> {
+        self.map.pipe_neighbours(loc)
+    }
+
+    /// All closed loops in the map, see [`crate::analysis::closed_loops`].
+    pub(crate) fn find_all_loops(&self) -> Vec<Vec<Location>> {
+        self.map.find_all_loops()
+    }
+
+    /// An index to look up whether tiles are inside or outside the loop.
+    /// None if there is no loop.
+    pub fn loop_index(&self) -> Option<LoopIndex> {
+        self.path.as_deref().map(LoopIndex::new)
+    }
+
+    /// The pipe hidden under `S`, deduced from the loop through it.
+    /// None if there is no loop.
+    pub fn start_tile(&self) -> Option<u8> {
+        self.path.as_deref().map(|path| self.map.start_tile(path))
+    }
+
+    /// A copy of the map with `S` replaced by the pipe hidden under it,
+    /// one line per row. None if there is no loop.
+    pub fn normalized(&self) -> Option<String> {
+        self.start_tile().map(|start_tile| self.rows(start_tile))
+    }
+
+    /// A copy of the map as it was read, with `S` kept and short rows
+    /// padded with ground, one line per row. This is input every solver
+    /// reads the same way, whatever [`ParseMode`] the map was parsed in.
+    pub fn padded(&self) -> String {
+        self.rows(b'S')
+    }
+
+    /// The tiles of the map with the given tile at the start, one line per
+    /// row.
+    fn rows(&self, start_tile: u8) -> String {
+        let mut result =
+            String::with_capacity((self.width() + 1) * self.height());
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let loc = Location { x, y };
+                if loc == self.map.starting_pos {
+                    result.push(char::from(start_tile));
+                } else {
+                    result.push(char::from(self.map.get(loc)));
+                }
+            }
+            result.push('\n');
+        }
+        result
+    }
+}
+
+impl<'a> TryFrom<&'a str> for PipeMap<'a> {
+    type Error = ParseError;
+
+    fn try_from(input: &'a str) -> Result<Self, Self::Error> {
+        PipeMap::new(input)
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for PipeMap<'a> {
+    type Error = ParseError;
+
+    fn try_from(input: &'a [u8]) -> Result<Self, Self::Error> {
+        PipeMap::from_bytes(input)
+    }
+}
+
+/// Computes the number of enclosed tiles of the given path.
+/// This expects that the last point in the list is
+/// the same as the first point in the list, like in the return
+/// value of map.find_loop(). This function may panic with
+/// integer overflow, depending on the range of x and y coordinates
+/// in the path. So keep your map size reasonable.
+pub(crate) fn shoelace_with_picks_theorem(path: &[Location]) -> usize {
+    let n = path.len();
+    let area = shoelace_sum(path).unsigned_abs() / 2;
+    // Pick's theorem
+    area + 1 - (n - 1) / 2
+}
+
+/// Twice the signed area of the given closed path by the shoelace formula.
+/// It is positive if the path runs clockwise on the map as it is printed,
+/// with the rows going down, and negative if it runs counter-clockwise.
+pub(crate) fn shoelace_sum(path: &[Location]) -> isize {
+    // we need to switch to isize arithmetic as area may
+    // become negative
+    path.windows(2).fold(0, |acc, pair| {
+        // a window always has two elements, so no bound checking
+        let &[current, next] = pair else { return acc };
+        let (xi, yi) = (current.x as isize, current.y as isize);
+        let (x_next, y_next) = (next.x as isize, next.y as isize);
+        acc + (yi + y_next) * (xi - x_next)
+    })
+}
+
+/// Whether a tile of the loop crosses it for a scan along its row, where
+/// the tiles after an odd number of crossings are inside. Every tile with
+/// a pipe leading north crosses the loop. Looking at the north side only
+/// counts a horizontal run like L-7 as a crossing and L-J as none. `S`
+/// counts as `start_tile`, the pipe hidden under it.
+pub(crate) fn crosses_loop(tile: u8, start_tile: u8) -> bool {
+    let tile = if tile == b'S' { start_tile } else { tile };
+    matches!(tile, b'|' | b'L' | b'J')
+}
+
+/// The contribution of the edge from `from` to `to` to twice the area of
+/// a polygon by the shoelace formula.
+fn shoelace_term(from: Location, to: Location) -> Result<i128, AreaOverflow> {
+    let coordinate = |c: usize| i128::try_from(c).map_err(|_| AreaOverflow);
+    let height = coordinate(from.y)?
+        .checked_add(coordinate(to.y)?)
+        .ok_or(AreaOverflow)?;
+    let width = coordinate(from.x)? - coordinate(to.x)?;
+    height.checked_mul(width).ok_or(AreaOverflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{read_input, SAMPLES};
+
+    #[test]
+    fn test_connected_to() {
+        let input = ".....
+.S-7.
+.|.|.
+.L-J.
+.....        
+";
+        let map = parse(input);
+        let start = map.starting_pos;
+        let connected = map.connected_to(&start);
+        assert_eq!(connected.len(), 2);
+        let expected = vec![
+            (Location { x: 1, y: 2 }, Direction::North),
+            (Location { x: 2, y: 1 }, Direction::West),
+        ];
+        assert_eq!(connected, expected);
+    }
+
+    #[test]
+    fn test_connected_to2() {
+        let input = "-L|F7
+7S-7|
+L|7||
+-L-J|
+L|-JF
+";
+        let map = parse(input);
+        let start = map.starting_pos;
+
+        let connected = map.connected_to(&start);
+        assert_eq!(connected.len(), 2);
+        let expected = vec![
+            (Location { x: 1, y: 2 }, Direction::North),
+            (Location { x: 2, y: 1 }, Direction::West),
+        ];
+        assert_eq!(connected, expected);
+    }
+
+    #[test]
+    fn test_find_loop() {
+        let input = ".....
+.S-7.
+.|.|.
+.L-J.
+.....        
+";
+        let map = parse(input);
+        let steps = map.find_loop().unwrap().len() / 2;
+        assert_eq!(steps, 4);
+    }
+
+    #[test]
+    fn test_shoelace() {
+        let input = ".....
+.S-7.
+.|.|.
+.L-J.
+.....        
+";
+        let map = parse(input);
+        let steps = map.find_loop().unwrap();
+        let area = shoelace_with_picks_theorem(&steps);
+        assert_eq!(area, 1);
+    }
+
+    #[test]
+    fn test_shoelace2() {
+        let input = "...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+...........";
+        let map = parse(input);
+        let steps = map.find_loop().unwrap();
+        let area = shoelace_with_picks_theorem(&steps);
+        assert_eq!(area, 4);
+    }
+    #[test]
+    fn test_shoelace_smallest_loop() {
+        let map = parse("S7\nLJ\n");
+        let steps = map.find_loop().unwrap();
+        assert_eq!(shoelace_with_picks_theorem(&steps), 0);
+        assert_eq!(map.checked_enclosed_count(), Ok(0));
+    }
+
+    #[test]
+    fn test_checked_enclosed_count() {
+        let input = "...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+...........";
+        assert_eq!(parse(input).checked_enclosed_count(), Ok(4));
+        assert_eq!(parse("S-7\n..|\n").checked_enclosed_count(), Ok(0));
+        let buf = read_input();
+        assert_eq!(parse(&buf).checked_enclosed_count(), Ok(433));
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn test_shoelace_term_overflow() {
+        let corner = Location {
+            x: usize::MAX,
+            y: usize::MAX,
+        };
+        let next = Location {
+            x: usize::MAX - 1,
+            y: usize::MAX,
+        };
+        assert!(shoelace_term(corner, next).is_ok());
+        let far = Location { x: 0, ..corner };
+        assert_eq!(shoelace_term(corner, far), Err(AreaOverflow));
+    }
+
+    #[test]
+    fn test_find_loop2() {
+        let input = "..F7.
+.FJ|.
+SJ.L7
+|F--J
+LJ...
+";
+        let map = parse(input);
+        let steps = map.find_loop().unwrap().len() / 2;
+        assert_eq!(steps, 8);
+    }
+
+    #[test]
+    fn test_next_tile() {
+        let input = ".....
+.F-7.
+.|.|.
+.L-J.
+.....        
+";
+        let map = parse(input);
+        let result = map
+            .next_location(&Location { x: 1, y: 1 }, Direction::South)
+            .unwrap();
+        assert_eq!(result, (Location { x: 2, y: 1 }, Direction::West));
+        let result = map
+            .next_location(&Location { x: 1, y: 1 }, Direction::East)
+            .unwrap();
+        assert_eq!(result, (Location { x: 1, y: 2 }, Direction::North));
+        let result = map
+            .next_location(&Location { x: 2, y: 1 }, Direction::West)
+            .unwrap();
+        assert_eq!(result, (Location { x: 3, y: 1 }, Direction::West));
+        let result = map
+            .next_location(&Location { x: 2, y: 1 }, Direction::East)
+            .unwrap();
+        assert_eq!(result, (Location { x: 1, y: 1 }, Direction::East));
+        let result = map
+            .next_location(&Location { x: 3, y: 1 }, Direction::West)
+            .unwrap();
+        assert_eq!(result, (Location { x: 3, y: 2 }, Direction::North));
+        let result = map
+            .next_location(&Location { x: 3, y: 1 }, Direction::South)
+            .unwrap();
+        assert_eq!(result, (Location { x: 2, y: 1 }, Direction::East));
+        let result = map
+            .next_location(&Location { x: 1, y: 2 }, Direction::North)
+            .unwrap();
+        assert_eq!(result, (Location { x: 1, y: 3 }, Direction::North));
+        let result = map
+            .next_location(&Location { x: 1, y: 2 }, Direction::South)
+            .unwrap();
+        assert_eq!(result, (Location { x: 1, y: 1 }, Direction::South));
+        let result = map
+            .next_location(&Location { x: 1, y: 3 }, Direction::North)
+            .unwrap();
+        assert_eq!(result, (Location { x: 2, y: 3 }, Direction::West));
+        let result = map
+            .next_location(&Location { x: 1, y: 3 }, Direction::East)
+            .unwrap();
+        assert_eq!(result, (Location { x: 1, y: 2 }, Direction::South));
+        let result = map
+            .next_location(&Location { x: 3, y: 3 }, Direction::North)
+            .unwrap();
+        assert_eq!(result, (Location { x: 2, y: 3 }, Direction::East));
+        let result = map
+            .next_location(&Location { x: 3, y: 3 }, Direction::West)
+            .unwrap();
+        assert_eq!(result, (Location { x: 3, y: 2 }, Direction::South));
+    }
+
+    #[test]
+    fn test_pipe_map() {
+        let input = "..F7.
+.FJ|.
+SJ.L7
+|F--J
+LJ...
+";
+        let map = PipeMap::new(input).unwrap();
+        assert_eq!(map.width(), 5);
+        assert_eq!(map.height(), 5);
+        assert_eq!(map.start(), Location { x: 0, y: 2 });
+        assert_eq!(map.tile(Location { x: 3, y: 2 }), Some(b'L'));
+        assert_eq!(map.tile(Location { x: 5, y: 2 }), None);
+        let path = map.loop_path().unwrap();
+        assert_eq!(path.len(), 17);
+        assert_eq!(path.first(), Some(&map.start()));
+        assert_eq!(path.last(), Some(&map.start()));
+        assert_eq!(map.farthest_distance(), 8);
+        assert_eq!(map.enclosed_count(), 1);
+    }
+
+    #[test]
+    fn test_pipe_map_from_bytes() {
+        let map =
+            PipeMap::try_from(&b".....\r\n.S-7.\r\n.|.|.\r\n.L-J.\r\n"[..])
+                .unwrap();
+        assert_eq!(map.width(), 5);
+        assert_eq!(map.height(), 4);
+        assert_eq!(map.farthest_distance(), 4);
+        assert_eq!(map.enclosed_count(), 1);
+        let map = PipeMap::try_from("S-7\n..|\n").unwrap();
+        assert_eq!(map.loop_path(), None);
+        assert_eq!(map.enclosed_count(), 0);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let input = b".....  \r\n.S-7.\r\r\n.|.|\t\n.L-J.\n...\n\n  \n";
+        assert_eq!(
+            PipeMap::from_bytes(input).err(),
+            Some(ParseError::IllegalByte {
+                byte: b' ',
+                row: 0,
+                column: 5
+            })
+        );
+        let map = PipeMap::with_mode(input, ParseMode::Normalize).unwrap();
+        assert_eq!(map.width(), 5);
+        assert_eq!(map.height(), 5);
+        assert_eq!(map.ragged_rows(), &[2, 4]);
+        assert_eq!(map.tile(Location { x: 4, y: 2 }), Some(b'.'));
+        assert_eq!(map.tile(Location { x: 4, y: 4 }), Some(b'.'));
+        assert_eq!(map.tile(Location { x: 5, y: 4 }), None);
+        assert_eq!(map.farthest_distance(), 4);
+        assert_eq!(map.enclosed_count(), 1);
+        assert_eq!(
+            map.normalized().as_deref(),
+            Some(".....\n.F-7.\n.|.|.\n.L-J.\n.....\n")
+        );
+        assert_eq!(map.padded(), ".....\n.S-7.\n.|.|.\n.L-J.\n.....\n");
+        let map =
+            PipeMap::with_mode(b"S-7\n|.|\nL-J", ParseMode::Normalize).unwrap();
+        assert!(map.ragged_rows().is_empty());
+        assert_eq!(map.padded(), "S-7\n|.|\nL-J\n");
+        assert_eq!(
+            PipeMap::with_mode(b" \r\n\n", ParseMode::Normalize).err(),
+            Some(ParseError::EmptyInput)
+        );
+        assert_eq!(
+            PipeMap::with_mode(b"S- 7\n", ParseMode::Normalize).err(),
+            Some(ParseError::IllegalByte {
+                byte: b' ',
+                row: 0,
+                column: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_normalize_input() {
+        let buf = read_input().replace('\n', " \r\n");
+        let map =
+            PipeMap::with_mode(buf.as_bytes(), ParseMode::Normalize).unwrap();
+        assert!(map.ragged_rows().is_empty());
+        assert_eq!(map.farthest_distance(), 6778);
+        assert_eq!(map.enclosed_count(), 433);
+    }
+
+    #[test]
+    fn test_strict_rejects_ragged_rows() {
+        assert_eq!(
+            PipeMap::from_bytes(b".S-7.\n.|.|.  \n").err(),
+            Some(ParseError::RaggedRow {
+                row: 1,
+                width: 7,
+                expected: 5
+            })
+        );
+        assert_eq!(
+            PipeMap::with_mode(b".S-7.\n.L-J\n", ParseMode::Strict).err(),
+            Some(ParseError::RaggedRow {
+                row: 1,
+                width: 4,
+                expected: 5
+            })
+        );
+        assert_eq!(
+            PipeMap::from_bytes(b"S7\r\nLJ\r\n\r\n").err(),
+            Some(ParseError::RaggedRow {
+                row: 2,
+                width: 0,
+                expected: 2
+            })
+        );
+        assert!(PipeMap::from_bytes(b"S7\r\nLJ").is_ok());
+    }
+
+    #[test]
+    fn test_start_tile() {
+        let map = PipeMap::new(".....\n.S-7.\n.|.|.\n.L-J.\n.....\n").unwrap();
+        assert_eq!(map.start_tile(), Some(b'F'));
+        assert_eq!(
+            map.normalized().as_deref(),
+            Some(".....\n.F-7.\n.|.|.\n.L-J.\n.....\n")
+        );
+        let map = PipeMap::new("..F7.\n.FJ|.\nSJ.L7\n|F--J\nLJ...").unwrap();
+        assert_eq!(map.start_tile(), Some(b'F'));
+        let map = PipeMap::new("F-7\n|.S\nL-J").unwrap();
+        assert_eq!(map.start_tile(), Some(b'|'));
+        let map = PipeMap::new("F-S\n|.|\nL-J").unwrap();
+        assert_eq!(map.start_tile(), Some(b'7'));
+        let map = PipeMap::new("F-7\n|.|\nS-J").unwrap();
+        assert_eq!(map.start_tile(), Some(b'L'));
+        let map = PipeMap::new("F-7\n|.|\nL-S").unwrap();
+        assert_eq!(map.start_tile(), Some(b'J'));
+        let map = PipeMap::new("F-7\n|.|\nLSJ").unwrap();
+        assert_eq!(map.start_tile(), Some(b'-'));
+        let map = PipeMap::new("S-7\n..|\n").unwrap();
+        assert_eq!(map.start_tile(), None);
+        assert_eq!(map.normalized(), None);
+    }
+
+    #[test]
+    fn test_start_tile_input() {
+        let input = read_input();
+        let map = PipeMap::new(&input).unwrap();
+        assert_eq!(map.start_tile(), Some(b'-'));
+        let normalized = map.normalized().unwrap();
+        assert_eq!(normalized.len(), input.len());
+        assert!(!normalized.contains('S'));
+    }
+
+    #[test]
+    fn test_enclosed_tiles() {
+        for input in SAMPLES {
+            // the first sample has trailing blanks in its last row, another
+            // one marks some ground tiles as inside with I
+            let trimmed = input.replace(' ', "").replace('I', ".");
+            let map = PipeMap::new(&trimmed).unwrap();
+            assert_eq!(map.enclosed_tiles().len(), crate::p2(input));
+        }
+        let map = PipeMap::new(".....\n.S-7.\n.|.|.\n.L-J.\n.....\n").unwrap();
+        assert_eq!(map.enclosed_tiles(), vec![Location { x: 2, y: 2 }]);
+        let map = PipeMap::new("S-7\n..|\n").unwrap();
+        assert!(map.enclosed_tiles().is_empty());
+    }
+
+    #[test]
+    fn test_enclosed_tiles_input() {
+        let input = read_input();
+        let map = PipeMap::new(&input).unwrap();
+        assert_eq!(map.enclosed_tiles().len(), crate::p2(&input));
+        assert_eq!(map.enclosed_tiles().len(), 433);
+    }
+
+    #[test]
+    fn test_farthest() {
+        let map = PipeMap::new("..F7.\n.FJ|.\nSJ.L7\n|F--J\nLJ...\n").unwrap();
+        assert_eq!(map.farthest(), vec![Location { x: 4, y: 2 }]);
+        let distances = map.loop_distances();
+        assert_eq!(distances.len(), 16);
+        assert_eq!(distances.iter().max(), Some(&map.farthest_distance()));
+        let distance_map = map.distance_map();
+        assert_eq!(distance_map[2 * 5], Some(0));
+        assert_eq!(distance_map[2 * 5 + 4], Some(8));
+        assert_eq!(distance_map[3 * 5 + 1], Some(4));
+        assert_eq!(distance_map[0], None);
+        assert_eq!(distance_map.iter().flatten().count(), 16);
+        let map = PipeMap::new("S-7\n..|\n").unwrap();
+        assert!(map.farthest().is_empty());
+        assert!(map.loop_distances().is_empty());
+        assert!(map.distance_map().iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn test_farthest_input() {
+        let input = read_input();
+        let map = PipeMap::new(&input).unwrap();
+        let farthest = map.farthest();
+        assert_eq!(farthest.len(), 1);
+        let distance_map = map.distance_map();
+        let at = |loc: Location| distance_map[loc.y * map.width() + loc.x];
+        assert_eq!(at(farthest[0]), Some(6778));
+        assert_eq!(distance_map.iter().flatten().max(), Some(&6778));
+    }
+}
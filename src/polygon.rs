@@ -0,0 +1,228 @@
+use std::collections::HashSet;
+
+use crate::map::{parse, Map};
+use crate::{Location, PipeMap};
+
+/// A loop stored by its corners only, the tiles where it changes
+/// direction. The straight runs of pipes between the corners follow from
+/// them, so the larger a loop and the longer its straight runs, the less
+/// this takes compared to the list of all tiles of the loop.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CornerPolygon {
+    corners: Vec<Location>,
+}
+
+impl CornerPolygon {
+    /// A polygon with the given corners, where each corner is in the same
+    /// row or column as the one before it and the last one is in the same
+    /// row or column as the first one. The loop must turn at every corner,
+    /// so its segments take turns between rows and columns, and it must
+    /// not run through a tile twice. None if the corners do not form a
+    /// loop like that.
+    pub fn new(corners: Vec<Location>) -> Option<CornerPolygon> {
+        let polygon = CornerPolygon { corners };
+        let aligned = polygon
+            .edges()
+            .all(|(from, to)| (from.x == to.x) != (from.y == to.y));
+        let horizontal = polygon
+            .edges()
+            .map(|(from, to)| from.y == to.y)
+            .collect::<Vec<_>>();
+        let n = horizontal.len();
+        let turning = (0..n).all(|i| horizontal[i] != horizontal[(i + 1) % n]);
+        if n < 4 || !aligned || !turning {
+            return None;
+        }
+        // a loop that crosses itself can enclose less than Pick's theorem
+        // allows for its length
+        let mut seen = HashSet::new();
+        polygon
+            .tiles()
+            .into_iter()
+            .all(|loc| seen.insert(loc))
+            .then_some(polygon)
+    }
+
+    /// Find the loop through `S` like [`crate::p1`] and [`crate::p2`] do,
+    /// but keep only its corners. None if there is no loop.
+    pub fn from_input(input: &str) -> Option<CornerPolygon> {
+        Self::from_parsed(&parse(input))
+    }
+
+    pub(crate) fn from_parsed(map: &Map) -> Option<CornerPolygon> {
+        map.find_loop_corners()
+            .map(|corners| CornerPolygon { corners })
+    }
+
+    /// The corners of the loop of the map. None if there is no loop.
+    pub fn from_map(map: &PipeMap) -> Option<CornerPolygon> {
+        let path = map.loop_path()?;
+        let start_tile = map.start_tile()?;
+        let corners = path[..path.len() - 1]
+            .iter()
+            .copied()
+            .filter(|&loc| {
+                let tile = match map.tile(loc) {
+                    Some(b'S') => start_tile,
+                    tile => tile.unwrap_or(b'.'),
+                };
+                b"LJ7F".contains(&tile)
+            })
+            .collect();
+        Some(CornerPolygon { corners })
+    }
+
+    /// The corners in the order the loop runs through them, the last one
+    /// connected to the first one.
+    pub fn corners(&self) -> &[Location] {
+        &self.corners
+    }
+
+    /// The number of tiles of the loop, which is the sum of the lengths of
+    /// its straight segments.
+    pub fn length(&self) -> usize {
+        self.edges()
+            .map(|(from, to)| from.x.abs_diff(to.x) + from.y.abs_diff(to.y))
+            .sum()
+    }
+
+    /// The number of tiles enclosed by the loop, by the shoelace formula
+    /// over the corners and Pick's theorem with the length of the loop.
+    pub fn enclosed_count(&self) -> usize {
+        let area = self
+            .edges()
+            .fold(0, |acc, (current, next)| {
+                let (xi, yi) = (current.x as isize, current.y as isize);
+                let (x_next, y_next) = (next.x as isize, next.y as isize);
+                acc + (yi + y_next) * (xi - x_next)
+            })
+            .unsigned_abs()
+            / 2;
+        // Pick's theorem
+        area + 1 - self.length() / 2
+    }
+
+    /// All tiles of the loop, starting at the first corner.
+    pub fn tiles(&self) -> Vec<Location> {
+        let mut tiles = Vec::with_capacity(self.length());
+        for (from, to) in self.edges() {
+            let mut loc = from;
+            while loc != to {
+                tiles.push(loc);
+                loc.x = step_towards(loc.x, to.x);
+                loc.y = step_towards(loc.y, to.y);
+            }
+        }
+        tiles
+    }
+
+    /// The straight segments of the loop, from one corner to the next.
+    fn edges(&self) -> impl Iterator<Item = (Location, Location)> + '_ {
+        let next = self.corners.iter().cycle().skip(1);
+        self.corners.iter().copied().zip(next.copied())
+    }
+}
+
+/// The coordinate one step closer to the target.
+fn step_towards(from: usize, to: usize) -> usize {
+    match from.cmp(&to) {
+        std::cmp::Ordering::Less => from + 1,
+        std::cmp::Ordering::Equal => from,
+        std::cmp::Ordering::Greater => from - 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::read_input;
+
+    #[test]
+    fn test_from_input() {
+        let polygon =
+            CornerPolygon::from_input(".....\n.S-7.\n.|.|.\n.L-J.\n.....\n")
+                .unwrap();
+        assert_eq!(
+            polygon.corners(),
+            &[
+                Location { x: 1, y: 1 },
+                Location { x: 1, y: 3 },
+                Location { x: 3, y: 3 },
+                Location { x: 3, y: 1 },
+            ]
+        );
+        assert_eq!(polygon.length(), 8);
+        assert_eq!(polygon.enclosed_count(), 1);
+        assert_eq!(polygon.tiles().len(), 8);
+        // S hides a straight pipe
+        let polygon = CornerPolygon::from_input("F-7\n|.S\nL-J\n").unwrap();
+        assert_eq!(polygon.corners().len(), 4);
+        assert!(!polygon.corners().contains(&Location { x: 2, y: 1 }));
+        assert_eq!(polygon.length(), 8);
+        assert_eq!(CornerPolygon::from_input("S-7\n..|\n"), None);
+    }
+
+    #[test]
+    fn test_new() {
+        let corners = vec![
+            Location { x: 0, y: 0 },
+            Location { x: 4, y: 0 },
+            Location { x: 4, y: 2 },
+            Location { x: 0, y: 2 },
+        ];
+        let polygon = CornerPolygon::new(corners.clone()).unwrap();
+        assert_eq!(polygon.length(), 12);
+        assert_eq!(polygon.enclosed_count(), 3);
+        assert_eq!(
+            polygon.tiles()[..3],
+            [
+                Location { x: 0, y: 0 },
+                Location { x: 1, y: 0 },
+                Location { x: 2, y: 0 }
+            ]
+        );
+        assert_eq!(CornerPolygon::new(corners[..3].to_vec()), None);
+        let mut skewed = corners;
+        skewed[2] = Location { x: 3, y: 2 };
+        assert_eq!(CornerPolygon::new(skewed), None);
+    }
+
+    #[test]
+    fn test_new_rejects_bad_turns() {
+        let corners = |corners: &[(usize, usize)]| {
+            corners.iter().map(|&(x, y)| Location { x, y }).collect()
+        };
+        // turning back
+        let backtracking = corners(&[(0, 0), (2, 0), (2, 1), (2, 0)]);
+        assert_eq!(CornerPolygon::new(backtracking), None);
+        // going straight on at a corner
+        let collinear = corners(&[(0, 0), (1, 0), (2, 0), (2, 1), (0, 1)]);
+        assert_eq!(CornerPolygon::new(collinear), None);
+        // crossing itself, so the two halves cancel out in the shoelace sum
+        let crossing =
+            corners(&[(0, 1), (2, 1), (2, 2), (1, 2), (1, 0), (0, 0)]);
+        assert_eq!(CornerPolygon::new(crossing), None);
+        let square = corners(&[(0, 0), (1, 0), (1, 1), (0, 1)]);
+        assert_eq!(CornerPolygon::new(square).unwrap().enclosed_count(), 0);
+    }
+
+    #[test]
+    fn test_corners_input() {
+        let buf = read_input();
+        let polygon = CornerPolygon::from_input(&buf).unwrap();
+        assert_eq!(polygon.length(), 13556);
+        assert_eq!(polygon.enclosed_count(), 433);
+        let map = PipeMap::new(&buf).unwrap();
+        assert_eq!(CornerPolygon::from_map(&map), Some(polygon.clone()));
+        let path = map.loop_path().unwrap();
+        let tiles = polygon.tiles();
+        let first = path.iter().position(|&loc| loc == tiles[0]).unwrap();
+        let rotated = path[first..path.len() - 1]
+            .iter()
+            .chain(&path[..first])
+            .copied()
+            .collect::<Vec<_>>();
+        assert_eq!(tiles, rotated);
+        assert!(polygon.corners().len() < path.len());
+    }
+}
@@ -0,0 +1,414 @@
+use std::io::{self, Write};
+
+use crate::{Containment, Location, PipeMap};
+
+/// What a tile shows in a raster image.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum TileClass {
+    /// The starting tile `S`.
+    Start,
+    /// A pipe of the loop.
+    Loop,
+    /// A tile enclosed by the loop, whatever is on it.
+    Inside,
+    /// A pipe outside of the loop.
+    Junk,
+    /// Ground outside of the loop.
+    Outside,
+}
+
+impl TileClass {
+    /// The colour of the class, as an index into [`PALETTE`].
+    fn index(self) -> u8 {
+        self as u8
+    }
+}
+
+/// The RGB colours of the tile classes, in the order of [`TileClass`].
+const PALETTE: [[u8; 3]; 5] = [
+    [0xf9, 0xa8, 0x25],
+    [0x2e, 0x7d, 0x32],
+    [0xe5, 0x73, 0x73],
+    [0xbd, 0xbd, 0xbd],
+    [0xff, 0xff, 0xff],
+];
+
+/// A map painted as a bitmap, each tile as a square block of pixels in the
+/// colour of its [`TileClass`]. Only the classes of the tiles are kept,
+/// pixels are produced row by row while writing, so images of large maps
+/// need little memory.
+#[derive(Debug, Clone)]
+pub struct Raster {
+    columns: usize,
+    rows: usize,
+    tile_size: usize,
+    classes: Vec<TileClass>,
+}
+
+impl Raster {
+    /// Classify the tiles of the map, painting each of them with
+    /// `tile_size` x `tile_size` pixels.
+    pub fn new(map: &PipeMap, tile_size: usize) -> Raster {
+        let (columns, rows) = (map.width(), map.height());
+        let classes = map
+            .containment_map()
+            .into_iter()
+            .enumerate()
+            .map(|(i, containment)| {
+                let loc = Location {
+                    x: i % columns,
+                    y: i / columns,
+                };
+                match containment {
+                    Containment::OnLoop if loc == map.start() => {
+                        TileClass::Start
+                    }
+                    Containment::OnLoop => TileClass::Loop,
+                    Containment::Inside => TileClass::Inside,
+                    Containment::Outside if map.tile(loc) == Some(b'.') => {
+                        TileClass::Outside
+                    }
+                    Containment::Outside => TileClass::Junk,
+                }
+            })
+            .collect();
+        Raster {
+            columns,
+            rows,
+            tile_size: tile_size.max(1),
+            classes,
+        }
+    }
+
+    /// The width of the image in pixels.
+    pub fn width(&self) -> usize {
+        self.columns * self.tile_size
+    }
+
+    /// The height of the image in pixels.
+    pub fn height(&self) -> usize {
+        self.rows * self.tile_size
+    }
+
+    /// The class of the tile painted at the given pixel.
+    pub fn class_at(&self, x: usize, y: usize) -> TileClass {
+        self.classes[y / self.tile_size * self.columns + x / self.tile_size]
+    }
+
+    /// The palette indices of a row of pixels.
+    fn pixel_row(&self, y: usize, row: &mut Vec<u8>) {
+        row.clear();
+        let first = y / self.tile_size * self.columns;
+        for class in &self.classes[first..first + self.columns] {
+            row.resize(row.len() + self.tile_size, class.index());
+        }
+    }
+
+    /// Write the image as binary PPM (P6).
+    pub fn write_ppm(&self, mut out: impl Write) -> io::Result<()> {
+        write!(out, "P6\n{} {}\n255\n", self.width(), self.height())?;
+        let mut row = Vec::with_capacity(self.width());
+        let mut rgb = Vec::with_capacity(self.width() * 3);
+        for y in 0..self.height() {
+            self.pixel_row(y, &mut row);
+            rgb.clear();
+            rgb.extend(row.iter().flat_map(|&i| PALETTE[i as usize]));
+            out.write_all(&rgb)?;
+        }
+        Ok(())
+    }
+
+    /// Write the image as PNG with a palette. The pixels are compressed
+    /// with run-length encoding only, which suits the large areas of a
+    /// single colour in these images.
+    pub fn write_png(&self, mut out: impl Write) -> io::Result<()> {
+        out.write_all(b"\x89PNG\r\n\x1a\n")?;
+        let mut header = Vec::with_capacity(13);
+        header.extend((self.width() as u32).to_be_bytes());
+        header.extend((self.height() as u32).to_be_bytes());
+        // bit depth 8, colour type 3 (palette), default compression,
+        // filtering and no interlacing
+        header.extend([8, 3, 0, 0, 0]);
+        write_chunk(&mut out, b"IHDR", &header)?;
+        write_chunk(&mut out, b"PLTE", &PALETTE.concat())?;
+
+        let mut deflate = Deflate::new();
+        let mut adler = Adler32::default();
+        let mut row = Vec::with_capacity(self.width() + 1);
+        for y in 0..self.height() {
+            self.pixel_row(y, &mut row);
+            // every row starts with its filter type, none
+            row.insert(0, 0);
+            adler.update(&row);
+            deflate.compress(&row);
+            if deflate.out.len() >= 1 << 16 {
+                write_chunk(&mut out, b"IDAT", &deflate.out)?;
+                deflate.out.clear();
+            }
+        }
+        deflate.finish();
+        deflate.out.extend(adler.finish().to_be_bytes());
+        write_chunk(&mut out, b"IDAT", &deflate.out)?;
+        write_chunk(&mut out, b"IEND", &[])
+    }
+}
+
+/// Write a PNG chunk with its length and checksum.
+fn write_chunk(
+    out: &mut impl Write,
+    kind: &[u8; 4],
+    data: &[u8],
+) -> io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    let mut crc = Crc32::default();
+    crc.update(kind);
+    crc.update(data);
+    out.write_all(&crc.finish().to_be_bytes())
+}
+
+#[derive(Debug)]
+struct Crc32(u32);
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Crc32(!0)
+    }
+}
+
+impl Crc32 {
+    fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u32::from(byte);
+            for _ in 0..8 {
+                let mask = (self.0 & 1).wrapping_neg();
+                self.0 = (self.0 >> 1) ^ (0xedb8_8320 & mask);
+            }
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        !self.0
+    }
+}
+
+#[derive(Debug)]
+struct Adler32 {
+    a: u32,
+    b: u32,
+}
+
+impl Default for Adler32 {
+    fn default() -> Self {
+        Adler32 { a: 1, b: 0 }
+    }
+}
+
+impl Adler32 {
+    fn update(&mut self, bytes: &[u8]) {
+        // 5552 bytes is the most that can be summed up without overflow
+        for chunk in bytes.chunks(5552) {
+            for &byte in chunk {
+                self.a += u32::from(byte);
+                self.b += self.a;
+            }
+            self.a %= 65521;
+            self.b %= 65521;
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        (self.b << 16) | self.a
+    }
+}
+
+/// A zlib stream of a single deflate block with the fixed Huffman codes,
+/// encoding runs of equal bytes as copies of the previous byte.
+#[derive(Debug)]
+struct Deflate {
+    out: Vec<u8>,
+    bits: u64,
+    bit_count: u32,
+}
+
+/// The base lengths of the length symbols 257 to 285.
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59,
+    67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+
+/// The number of extra bits of the length symbols 257 to 285.
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5,
+    5, 5, 5, 0,
+];
+
+impl Deflate {
+    fn new() -> Deflate {
+        let mut deflate = Deflate {
+            // deflate with a 32K window, no dictionary, fastest
+            out: vec![0x78, 0x01],
+            bits: 0,
+            bit_count: 0,
+        };
+        // the final block, compressed with fixed Huffman codes
+        deflate.write_bits(1, 1);
+        deflate.write_bits(1, 2);
+        deflate
+    }
+
+    /// Append bits, least significant first.
+    fn write_bits(&mut self, value: u32, count: u32) {
+        self.bits |= u64::from(value) << self.bit_count;
+        self.bit_count += count;
+        while self.bit_count >= 8 {
+            self.out.push(self.bits as u8);
+            self.bits >>= 8;
+            self.bit_count -= 8;
+        }
+    }
+
+    /// Append a Huffman code, most significant bit first.
+    fn write_code(&mut self, code: u32, length: u32) {
+        self.write_bits(code.reverse_bits() >> (32 - length), length);
+    }
+
+    fn write_symbol(&mut self, symbol: u16) {
+        let symbol = u32::from(symbol);
+        match symbol {
+            0..=143 => self.write_code(0x30 + symbol, 8),
+            144..=255 => self.write_code(0x190 + symbol - 144, 9),
+            256..=279 => self.write_code(symbol - 256, 7),
+            _ => self.write_code(0xc0 + symbol - 280, 8),
+        }
+    }
+
+    /// Copy the previous byte `length` times, 3 <= length <= 258.
+    fn write_repeat(&mut self, length: u16) {
+        let i = LENGTH_BASE.partition_point(|&base| base <= length) - 1;
+        self.write_symbol(257 + i as u16);
+        let extra = u32::from(LENGTH_EXTRA[i]);
+        self.write_bits(u32::from(length - LENGTH_BASE[i]), extra);
+        // distance code 0 is a distance of 1
+        self.write_code(0, 5);
+    }
+
+    fn compress(&mut self, bytes: &[u8]) {
+        for run in bytes.chunk_by(|a, b| a == b) {
+            self.write_symbol(u16::from(run[0]));
+            let mut rest = run.len() - 1;
+            while rest >= 3 {
+                let length = rest.min(258);
+                self.write_repeat(length as u16);
+                rest -= length;
+            }
+            for _ in 0..rest {
+                self.write_symbol(u16::from(run[0]));
+            }
+        }
+    }
+
+    /// End the block and pad the last byte.
+    fn finish(&mut self) {
+        self.write_symbol(256);
+        if self.bit_count > 0 {
+            self.write_bits(0, 8 - self.bit_count);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "7..........
+.S-------7.
+.|F-----7|.
+.||.-...||.
+.||.....||.
+.|L-7.F-J|.
+.|.-|.|..|.
+.L--J.L--J.
+...........
+";
+
+    #[test]
+    fn test_classes() {
+        let map = PipeMap::new(SAMPLE).unwrap();
+        let raster = Raster::new(&map, 3);
+        assert_eq!(raster.width(), 33);
+        assert_eq!(raster.height(), 27);
+        assert_eq!(raster.class_at(0, 0), TileClass::Junk);
+        assert_eq!(raster.class_at(3, 0), TileClass::Outside);
+        assert_eq!(raster.class_at(5, 5), TileClass::Start);
+        assert_eq!(raster.class_at(8, 3), TileClass::Loop);
+        assert_eq!(raster.class_at(12, 11), TileClass::Junk);
+        assert_eq!(raster.class_at(6, 20), TileClass::Inside);
+        assert_eq!(raster.class_at(11, 18), TileClass::Inside);
+    }
+
+    #[test]
+    fn test_write_ppm() {
+        let map = PipeMap::new("F-7\n|.|\nS-J\n").unwrap();
+        let mut ppm = Vec::new();
+        Raster::new(&map, 2).write_ppm(&mut ppm).unwrap();
+        assert!(ppm.starts_with(b"P6\n6 6\n255\n"));
+        let pixels = &ppm[11..];
+        assert_eq!(pixels.len(), 6 * 6 * 3);
+        let pixel = |x: usize, y: usize| &pixels[(y * 6 + x) * 3..][..3];
+        assert_eq!(pixel(0, 0), PALETTE[1]);
+        assert_eq!(pixel(2, 2), PALETTE[2]);
+        assert_eq!(pixel(3, 3), PALETTE[2]);
+        assert_eq!(pixel(1, 5), PALETTE[0]);
+    }
+
+    #[test]
+    fn test_checksums() {
+        let mut crc = Crc32::default();
+        crc.update(b"123456789");
+        assert_eq!(crc.finish(), 0xcbf4_3926);
+        let mut crc = Crc32::default();
+        crc.update(b"IEND");
+        assert_eq!(crc.finish(), 0xae42_6082);
+        let mut adler = Adler32::default();
+        adler.update(b"Wikipedia");
+        assert_eq!(adler.finish(), 0x11e6_0398);
+    }
+
+    #[test]
+    fn test_deflate() {
+        // the fixed Huffman codes of 'a' and the length symbols are known,
+        // check a literal followed by a run against a hand-made stream
+        let mut deflate = Deflate::new();
+        deflate.compress(b"aaaa");
+        deflate.finish();
+        // final fixed block: 1, 10 -> bits 0b011
+        // 'a' = 0x61 -> code 0x91 (8 bits), repeat 3 -> symbol 257, code
+        // 0000001 (7 bits), distance 1 -> 00000 (5 bits), end -> 0000000
+        assert_eq!(deflate.out, vec![0x78, 0x01, 0x4b, 0x04, 0x02, 0x00]);
+    }
+
+    #[test]
+    fn test_write_png() {
+        let map = PipeMap::new(SAMPLE).unwrap();
+        let mut png = Vec::new();
+        Raster::new(&map, 4).write_png(&mut png).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR"));
+        assert_eq!(&png[16..24], &[0, 0, 0, 44, 0, 0, 0, 36]);
+        assert!(png.ends_with(b"\0\0\0\0IEND\xae\x42\x60\x82"));
+        // walk the chunks and check their checksums
+        let mut rest = &png[8..];
+        let mut kinds = Vec::new();
+        while !rest.is_empty() {
+            let length = u32::from_be_bytes(rest[..4].try_into().unwrap());
+            let (chunk, tail) = rest[4..].split_at(length as usize + 4);
+            let mut crc = Crc32::default();
+            crc.update(chunk);
+            assert_eq!(crc.finish().to_be_bytes(), tail[..4]);
+            kinds.push(String::from_utf8_lossy(&chunk[..4]).into_owned());
+            rest = &tail[4..];
+        }
+        assert_eq!(kinds, vec!["IHDR", "PLTE", "IDAT", "IEND"]);
+    }
+}
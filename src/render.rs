@@ -0,0 +1,166 @@
+use crate::{Containment, Location, PipeMap};
+
+/// The characters used to draw a map.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum Style {
+    /// The pipe symbols of the puzzle, `I` for enclosed tiles.
+    Ascii,
+    /// Box-drawing characters for the loop, `•` for enclosed tiles.
+    #[default]
+    Unicode,
+}
+
+/// How to render a map with [`render`].
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub struct RenderOptions {
+    pub style: Style,
+    /// Colour loop, start and enclosed tiles with ANSI escape codes.
+    pub color: bool,
+}
+
+const LOOP_COLOR: &str = "\x1b[32m";
+const START_COLOR: &str = "\x1b[1;33m";
+const INSIDE_COLOR: &str = "\x1b[31m";
+const RESET: &str = "\x1b[0m";
+
+/// Draw the map for humans: the loop is drawn with its pipes, all other
+/// pipes are blanked out and enclosed tiles are marked, so the loop and
+/// its inside stand out even on large maps. Each row ends with a newline.
+pub fn render(map: &PipeMap, options: &RenderOptions) -> String {
+    let containment = map.containment_map();
+    let start = map.start();
+    let start_tile = map.start_tile().unwrap_or(b'S');
+    let mut result = String::with_capacity(
+        (map.width() * if options.color { 6 } else { 3 } + 1) * map.height(),
+    );
+    for y in 0..map.height() {
+        let mut current_color = None;
+        for x in 0..map.width() {
+            let loc = Location { x, y };
+            let (glyph, color) = match containment[y * map.width() + x] {
+                Containment::OnLoop if loc == start => {
+                    let glyph = match options.style {
+                        Style::Ascii => 'S',
+                        Style::Unicode => box_drawing(start_tile),
+                    };
+                    (glyph, Some(START_COLOR))
+                }
+                Containment::OnLoop => {
+                    let tile = map.tile(loc).unwrap_or(b'.');
+                    let glyph = match options.style {
+                        Style::Ascii => char::from(tile),
+                        Style::Unicode => box_drawing(tile),
+                    };
+                    (glyph, Some(LOOP_COLOR))
+                }
+                Containment::Inside => {
+                    let glyph = match options.style {
+                        Style::Ascii => 'I',
+                        Style::Unicode => '•',
+                    };
+                    (glyph, Some(INSIDE_COLOR))
+                }
+                Containment::Outside => (' ', None),
+            };
+            if options.color && color != current_color {
+                result.push_str(color.unwrap_or(RESET));
+                current_color = color;
+            }
+            result.push(glyph);
+        }
+        if options.color && current_color.is_some() {
+            result.push_str(RESET);
+        }
+        result.push('\n');
+    }
+    result
+}
+
+/// The box-drawing character for a pipe.
+fn box_drawing(tile: u8) -> char {
+    match tile {
+        b'|' => '│',
+        b'-' => '─',
+        b'L' => '└',
+        b'J' => '┘',
+        b'7' => '┐',
+        b'F' => '┌',
+        _ => char::from(tile),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "7..........
+.S-------7.
+.|F-----7|.
+.||.-...||.
+.||.....||.
+.|L-7.F-J|.
+.|.-|.|..|.
+.L--J.L--J.
+...........
+";
+
+    #[test]
+    fn test_render_unicode() {
+        let map = PipeMap::new(SAMPLE).unwrap();
+        let rendered = render(&map, &RenderOptions::default());
+        assert_eq!(rendered.lines().count(), 9);
+        assert!(rendered.lines().all(|line| line.chars().count() == 11));
+        assert_eq!(
+            rendered.lines().map(str::trim_end).collect::<Vec<_>>(),
+            vec![
+                "",
+                " ┌───────┐",
+                " │┌─────┐│",
+                " ││     ││",
+                " ││     ││",
+                " │└─┐ ┌─┘│",
+                " │••│ │••│",
+                " └──┘ └──┘",
+                "",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_ascii() {
+        let map = PipeMap::new(SAMPLE).unwrap();
+        let options = RenderOptions {
+            style: Style::Ascii,
+            color: false,
+        };
+        let rendered = render(&map, &options);
+        assert_eq!(rendered.lines().next(), Some("           "));
+        assert_eq!(rendered.lines().nth(1), Some(" S-------7 "));
+        assert_eq!(rendered.lines().nth(6), Some(" |II| |II| "));
+    }
+
+    #[test]
+    fn test_render_color() {
+        let map = PipeMap::new(SAMPLE).unwrap();
+        let options = RenderOptions {
+            style: Style::Ascii,
+            color: true,
+        };
+        let rendered = render(&map, &options);
+        assert_eq!(
+            rendered.lines().nth(1),
+            Some(" \x1b[1;33mS\x1b[32m-------7\x1b[0m ")
+        );
+        assert_eq!(
+            rendered.lines().nth(6),
+            Some(
+                " \x1b[32m|\x1b[31mII\x1b[32m|\x1b[0m \x1b[32m|\x1b[31mII\
+                 \x1b[32m|\x1b[0m "
+            )
+        );
+        assert_eq!(
+            rendered.lines().nth(7),
+            Some(" \x1b[32mL--J\x1b[0m \x1b[32mL--J\x1b[0m ")
+        );
+    }
+}
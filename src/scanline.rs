@@ -0,0 +1,70 @@
+use crate::map::{crosses_loop, Map};
+use crate::Location;
+
+/// Count the tiles enclosed by the given path, which is closed like the
+/// one returned by find_loop(), by scanning the rows of the map for
+/// crossings of the loop, see [`crosses_loop`]. Unlike
+/// [`crate::PipeMap::containment_map`], it only keeps one bit per tile.
+pub(crate) fn enclosed_count(map: &Map, path: &[Location]) -> usize {
+    let width = map.width();
+    let on_loop = LoopBits::new(path, width, map.height());
+    let start_tile = map.start_tile(path);
+    let mut count = 0;
+    for y in 0..map.height() {
+        let mut inside = false;
+        for x in 0..width {
+            if !on_loop.contains(y * width + x) {
+                count += usize::from(inside);
+            } else if crosses_loop(map.get(Location { x, y }), start_tile) {
+                inside = !inside;
+            }
+        }
+    }
+    count
+}
+
+/// A set of tiles of the map, row by row, with one bit per tile.
+struct LoopBits(Vec<u64>);
+
+impl LoopBits {
+    fn new(path: &[Location], width: usize, height: usize) -> LoopBits {
+        let mut bits = vec![0; (width * height).div_ceil(64)];
+        for loc in path {
+            let i = loc.y * width + loc.x;
+            bits[i / 64] |= 1 << (i % 64);
+        }
+        LoopBits(bits)
+    }
+
+    fn contains(&self, i: usize) -> bool {
+        self.0[i / 64] & (1 << (i % 64)) != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::parse;
+
+    #[test]
+    fn test_enclosed_count() {
+        let map = parse(".....\n.S-7.\n.|.|.\n.L-J.\n.....\n");
+        let path = map.find_loop().unwrap();
+        assert_eq!(enclosed_count(&map, &path), 1);
+        // S hides a | that crosses the loop
+        let map = parse("F-7\n|.S\n|.|\nL-J\n");
+        let path = map.find_loop().unwrap();
+        assert_eq!(enclosed_count(&map, &path), 2);
+    }
+
+    #[test]
+    fn test_loop_bits() {
+        let path = [Location { x: 1, y: 0 }, Location { x: 2, y: 9 }];
+        let bits = LoopBits::new(&path, 10, 10);
+        assert_eq!(bits.0.len(), 2);
+        assert!(bits.contains(1));
+        assert!(bits.contains(92));
+        assert!(!bits.contains(0));
+        assert!(!bits.contains(91));
+    }
+}
@@ -0,0 +1,119 @@
+use std::fmt::Write;
+
+use crate::{Location, PipeMap};
+
+/// How to draw a map with [`to_svg`].
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct SvgOptions {
+    /// The width and height of a tile in pixels.
+    pub scale: f64,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions { scale: 8.0 }
+    }
+}
+
+const BACKGROUND: &str = "#ffffff";
+const INSIDE: &str = "#f4c7c3";
+const LOOP: &str = "#2e7d32";
+const START: &str = "#f9a825";
+const FARTHEST: &str = "#1565c0";
+
+/// Draw the loop as an SVG image: the loop is a line through the centers
+/// of its tiles, its inside is filled, and `S` and the tiles farthest from
+/// it are marked. The drawing uses one unit per tile, so it keeps its
+/// proportions for any map and `scale` only sets the size of the image.
+pub fn to_svg(map: &PipeMap, options: &SvgOptions) -> String {
+    let (width, height) = (map.width(), map.height());
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" \
+         height=\"{}\" viewBox=\"0 0 {width} {height}\">",
+        width as f64 * options.scale,
+        height as f64 * options.scale,
+    );
+    let _ = writeln!(
+        svg,
+        "<rect width=\"{width}\" height=\"{height}\" fill=\"{BACKGROUND}\"/>"
+    );
+    if let Some(path) = map.loop_path() {
+        let points = points(path);
+        let _ = writeln!(
+            svg,
+            "<polygon points=\"{points}\" fill=\"{INSIDE}\" stroke=\"none\"/>"
+        );
+        let _ = writeln!(
+            svg,
+            "<polyline points=\"{points}\" fill=\"none\" stroke=\"{LOOP}\" \
+             stroke-width=\"0.3\" stroke-linejoin=\"round\"/>"
+        );
+        for loc in map.farthest() {
+            marker(&mut svg, loc, FARTHEST);
+        }
+    }
+    marker(&mut svg, map.start(), START);
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// The centers of the tiles on the path, as the points of an SVG shape.
+fn points(path: &[Location]) -> String {
+    let mut points = String::with_capacity(path.len() * 8);
+    for (i, loc) in path.iter().enumerate() {
+        if i > 0 {
+            points.push(' ');
+        }
+        let _ = write!(points, "{}.5,{}.5", loc.x, loc.y);
+    }
+    points
+}
+
+/// Mark a tile with a dot.
+fn marker(svg: &mut String, loc: Location, color: &str) {
+    let _ = writeln!(
+        svg,
+        "<circle cx=\"{}.5\" cy=\"{}.5\" r=\"0.4\" fill=\"{color}\"/>",
+        loc.x, loc.y
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_svg() {
+        let map = PipeMap::new(".....\n.S-7.\n.|.|.\n.L-J.\n.....\n").unwrap();
+        let svg = to_svg(&map, &SvgOptions { scale: 10.0 });
+        assert_eq!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"50\" \
+             height=\"50\" viewBox=\"0 0 5 5\">
+<rect width=\"5\" height=\"5\" fill=\"#ffffff\"/>
+<polygon points=\"1.5,1.5 1.5,2.5 1.5,3.5 2.5,3.5 3.5,3.5 3.5,2.5 3.5,1.5 \
+             2.5,1.5 1.5,1.5\" fill=\"#f4c7c3\" stroke=\"none\"/>
+<polyline points=\"1.5,1.5 1.5,2.5 1.5,3.5 2.5,3.5 3.5,3.5 3.5,2.5 3.5,1.5 \
+             2.5,1.5 1.5,1.5\" fill=\"none\" stroke=\"#2e7d32\" \
+             stroke-width=\"0.3\" stroke-linejoin=\"round\"/>
+<circle cx=\"3.5\" cy=\"3.5\" r=\"0.4\" fill=\"#1565c0\"/>
+<circle cx=\"1.5\" cy=\"1.5\" r=\"0.4\" fill=\"#f9a825\"/>
+</svg>
+"
+        );
+    }
+
+    #[test]
+    fn test_to_svg_without_loop() {
+        let map = PipeMap::new("S-7\n..|\n").unwrap();
+        let svg = to_svg(&map, &SvgOptions::default());
+        assert!(svg.starts_with(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"24\" \
+             height=\"16\" viewBox=\"0 0 3 2\">"
+        ));
+        assert!(!svg.contains("<poly"));
+        assert!(svg.contains("<circle cx=\"0.5\" cy=\"0.5\""));
+    }
+}